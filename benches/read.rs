@@ -0,0 +1,74 @@
+//! Rough timings of the read paths, run with `cargo bench --features thread_local_counter`
+//!
+//! This measures with [`Instant`] instead of a benchmark framework, to keep the crate free of dev-dependencies.
+//! Each case reports the median time per operation over a number of batches, expect some noise between runs.
+
+use std::time::Instant;
+
+use arcu::{
+    atomic::Arcu,
+    cache::{CachedReader, ReaderCache},
+    epoch_counters::GlobalEpochCounterPool,
+    Rcu,
+};
+
+const BATCH: u32 = 100_000;
+const BATCHES: usize = 31;
+
+/// The median time per operation in nanoseconds
+fn median_per_op(mut op: impl FnMut()) -> f64 {
+    // warm up, including registering the thread local epoch counter
+    for _ in 0..BATCH {
+        op();
+    }
+
+    let mut batches = (0..BATCHES)
+        .map(|_| {
+            let start = Instant::now();
+            for _ in 0..BATCH {
+                op();
+            }
+            start.elapsed().as_secs_f64() * 1e9 / f64::from(BATCH)
+        })
+        .collect::<Vec<_>>();
+    batches.sort_by(f64::total_cmp);
+    batches[BATCHES / 2]
+}
+
+fn report(name: &str, per_op: f64) {
+    println!("{name:<40} {per_op:>8.1} ns");
+}
+
+fn main() {
+    let rcu = Arcu::new(42u64, GlobalEpochCounterPool);
+
+    report(
+        "atomic::Arcu::read",
+        median_per_op(|| {
+            std::hint::black_box(rcu.read());
+        }),
+    );
+
+    report(
+        "Rcu::with_read_borrowed",
+        median_per_op(|| {
+            std::hint::black_box(rcu.with_read_borrowed(|value| *value));
+        }),
+    );
+
+    let mut cache = ReaderCache::new();
+    report(
+        "ReaderCache::read_cached",
+        median_per_op(|| {
+            std::hint::black_box(cache.read_cached(&rcu));
+        }),
+    );
+
+    let mut reader = CachedReader::new();
+    report(
+        "CachedReader::read",
+        median_per_op(|| {
+            std::hint::black_box(reader.read(&rcu));
+        }),
+    );
+}
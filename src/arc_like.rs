@@ -0,0 +1,203 @@
+//! This module contains the [`ArcLike`] trait to abstract over the reference counted pointer stored in an [`crate::atomic::Arcu`]
+//! and the [`ThinPtr`] trait to store pointers to unsized values in it
+
+use alloc::{boxed::Box, sync::Arc};
+
+/// A thread safe reference counted pointer that can be stored in an [`crate::atomic::Arcu`]
+///
+/// This is implemented for [`Arc`] and with the `triomphe` feature for `triomphe::Arc`,
+/// which has no weak count and as such less overhead per snapshot.
+///
+/// ## Safety
+/// - `into_raw` must return a pointer to the value, giving up one strong count without decrementing it
+/// - `as_ptr` must return the same pointer as `into_raw`, without giving up a strong count
+/// - `from_raw` must take back one strong count of a pointer returned by `into_raw`
+/// - `increment_strong_count` must increment the strong count of a pointer returned by `into_raw`
+/// - the value must neither be moved nor dropped while a strong count remains
+/// - the strong count must be updated atomically
+pub unsafe trait ArcLike: Sized {
+    /// The type of the shared value
+    type Target: ?Sized;
+
+    /// Consume the pointer, returning the raw pointer to the value without decrementing the strong count
+    fn into_raw(this: Self) -> *const Self::Target;
+
+    /// Get the raw pointer to the value
+    fn as_ptr(this: &Self) -> *const Self::Target;
+
+    /// Take back one strong count of the raw pointer
+    ///
+    /// ## Safety
+    /// - the pointer must have been returned by [`ArcLike::into_raw`]
+    /// - the caller must own one strong count of the pointer
+    unsafe fn from_raw(ptr: *const Self::Target) -> Self;
+
+    /// Increment the strong count of the raw pointer
+    ///
+    /// ## Safety
+    /// - the pointer must have been returned by [`ArcLike::into_raw`]
+    /// - the strong count must be at least one for the duration of this call
+    unsafe fn increment_strong_count(ptr: *const Self::Target);
+}
+
+// Safety:
+// all functions delegate to their Arc counterpart
+unsafe impl<T: ?Sized> ArcLike for Arc<T> {
+    type Target = T;
+
+    #[inline]
+    fn into_raw(this: Self) -> *const T {
+        Arc::into_raw(this)
+    }
+
+    #[inline]
+    fn as_ptr(this: &Self) -> *const T {
+        Arc::as_ptr(this)
+    }
+
+    #[inline]
+    unsafe fn from_raw(ptr: *const T) -> Self {
+        // Safety: the caller upholds the safety requirements
+        unsafe { Arc::from_raw(ptr) }
+    }
+
+    #[inline]
+    unsafe fn increment_strong_count(ptr: *const T) {
+        // Safety: the caller upholds the safety requirements
+        unsafe { Arc::increment_strong_count(ptr) }
+    }
+}
+
+// Safety:
+// all functions delegate to their triomphe::Arc counterpart
+#[cfg(feature = "triomphe")]
+unsafe impl<T> ArcLike for triomphe::Arc<T> {
+    type Target = T;
+
+    #[inline]
+    fn into_raw(this: Self) -> *const T {
+        triomphe::Arc::into_raw(this)
+    }
+
+    #[inline]
+    fn as_ptr(this: &Self) -> *const T {
+        this.as_ptr()
+    }
+
+    #[inline]
+    unsafe fn from_raw(ptr: *const T) -> Self {
+        // Safety: the caller upholds the safety requirements
+        unsafe { triomphe::Arc::from_raw(ptr) }
+    }
+
+    #[inline]
+    unsafe fn increment_strong_count(ptr: *const T) {
+        // Safety:
+        // - the caller upholds the safety requirements
+        // - the reconstructed arc is never dropped, so we only add the strong count of the clone
+        unsafe {
+            let arc = core::mem::ManuallyDrop::new(triomphe::Arc::from_raw(ptr));
+            let _clone = core::mem::ManuallyDrop::new(triomphe::Arc::clone(&arc));
+        }
+    }
+}
+
+/// A value type whose pointers can be stored in a single atomic, as required by [`crate::atomic::Arcu`]
+///
+/// Pointers to sized values are stored as they are.
+/// Pointers to slices and `str` carry their length, which doesn't fit into the atomic next to the address,
+/// so these are stored as thin pointers to a box holding the pointer, which is allocated on each write.
+/// Reads only load the pointer from the box, the value itself isn't moved, see [`ThinPtr::BOXED`].
+///
+/// ## Safety
+/// - `load_thin` and `from_thin` must return the pointer passed to `into_thin` when creating the thin pointer
+/// - thin pointers that have not been passed to `from_thin` yet must be distinct
+/// - when `BOXED` is false, `into_thin` must not allocate, `from_thin` must not deallocate
+///   and `load_thin` must not dereference the thin pointer
+pub unsafe trait ThinPtr {
+    /// Whether the thin pointers point to an allocation of their own,
+    /// which is deallocated by [`ThinPtr::from_thin`] and must be alive for [`ThinPtr::load_thin`]
+    const BOXED: bool;
+
+    /// Convert the pointer into a thin pointer
+    fn into_thin(ptr: *const Self) -> *mut ();
+
+    /// Get the pointer stored in the thin pointer
+    ///
+    /// ## Safety
+    /// - the thin pointer must have been returned by [`ThinPtr::into_thin`] and not passed to [`ThinPtr::from_thin`] yet
+    unsafe fn load_thin(thin: *mut ()) -> *const Self;
+
+    /// Convert the thin pointer back into the pointer, deallocating it when boxed
+    ///
+    /// ## Safety
+    /// - the thin pointer must have been returned by [`ThinPtr::into_thin`] and not passed to [`ThinPtr::from_thin`] yet
+    unsafe fn from_thin(thin: *mut ()) -> *const Self;
+}
+
+// Safety:
+// the pointer is the thin pointer
+unsafe impl<T> ThinPtr for T {
+    const BOXED: bool = false;
+
+    #[inline]
+    fn into_thin(ptr: *const T) -> *mut () {
+        ptr.cast_mut().cast()
+    }
+
+    #[inline]
+    unsafe fn load_thin(thin: *mut ()) -> *const T {
+        thin.cast_const().cast()
+    }
+
+    #[inline]
+    unsafe fn from_thin(thin: *mut ()) -> *const T {
+        thin.cast_const().cast()
+    }
+}
+
+// Safety:
+// the thin pointer is a box holding the pointer, each into_thin allocates a new box
+unsafe impl<T> ThinPtr for [T] {
+    const BOXED: bool = true;
+
+    #[inline]
+    fn into_thin(ptr: *const [T]) -> *mut () {
+        Box::into_raw(Box::new(ptr)).cast()
+    }
+
+    #[inline]
+    unsafe fn load_thin(thin: *mut ()) -> *const [T] {
+        // Safety: the caller ensures the box is still alive
+        unsafe { *thin.cast::<*const [T]>() }
+    }
+
+    #[inline]
+    unsafe fn from_thin(thin: *mut ()) -> *const [T] {
+        // Safety: the caller ensures the box is still alive and gives up the thin pointer
+        *unsafe { Box::from_raw(thin.cast::<*const [T]>()) }
+    }
+}
+
+// Safety:
+// the thin pointer is a box holding the pointer, each into_thin allocates a new box
+unsafe impl ThinPtr for str {
+    const BOXED: bool = true;
+
+    #[inline]
+    fn into_thin(ptr: *const str) -> *mut () {
+        Box::into_raw(Box::new(ptr)).cast()
+    }
+
+    #[inline]
+    unsafe fn load_thin(thin: *mut ()) -> *const str {
+        // Safety: the caller ensures the box is still alive
+        unsafe { *thin.cast::<*const str>() }
+    }
+
+    #[inline]
+    unsafe fn from_thin(thin: *mut ()) -> *const str {
+        // Safety: the caller ensures the box is still alive and gives up the thin pointer
+        *unsafe { Box::from_raw(thin.cast::<*const str>()) }
+    }
+}
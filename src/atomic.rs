@@ -4,37 +4,106 @@ extern crate alloc;
 
 #[cfg(feature = "thread_local_counter")]
 use core::ops::Deref;
+#[cfg(target_has_atomic = "64")]
+use core::sync::atomic::AtomicU64;
+#[cfg(any(target_has_atomic = "64", feature = "stats"))]
+use core::sync::atomic::AtomicUsize;
 use core::sync::atomic::{AtomicPtr, Ordering};
 use std::marker::PhantomData;
 
 use alloc::sync::Arc;
 
+use crate::arc_like::{ArcLike, ThinPtr};
 #[cfg(feature = "thread_local_counter")]
 use crate::epoch_counters::GlobalEpochCounterPool;
-use crate::epoch_counters::{EpochCounter, EpochCounterPool};
+use crate::epoch_counters::{
+    AdaptiveBackoff, Backoff, EpochCounter, EpochCounterPool, RcsGuard, WaitForEpochs,
+};
+#[cfg(feature = "thread_local_counter")]
+use crate::rcu_ref::RcuRef;
+use crate::reclaim::Unreclaimed;
 
 use super::Rcu;
 
 /// A Rcu based on an atomic pointer to an [`Arc`] and a [`EpochCounterPool`]
 ///
-pub struct Arcu<T, P> {
+/// The reference counted pointer defaults to [`Arc`], but can be any [`ArcLike`].
+/// The [`Rcu`] trait and [`RcuRef`](crate::rcu_ref::RcuRef) are only available with [`Arc`],
+/// other pointers use the `*_arc` methods instead.
+///
+/// Zero sized values are supported, as each [`Arc`] has its own allocation for the reference counts
+/// the values can still be told apart by their address, e.g. by [`Arcu::compare_exchange_arc`].
+///
+/// ## Unsized values
+/// Slices and `str` can be stored as `Arcu<[T], P>` and `Arcu<str, P>`, e.g. to replace a shared `Arc<[Config]>`
+/// without wrapping it in a [`Vec`]. As their pointers don't fit into a single atomic,
+/// each write boxes the pointer, which reads load before incrementing the strong count, see [`ThinPtr`].
+/// Comparing with the active value loads the pointer from its box, which is only safe within the read critical section,
+/// so [`Arcu::compare_exchange_arc`] is only available for sized values, while [`Rcu::try_update`] works for both.
+///
+/// ## Interior mutability
+/// The Arcu is [`Sync`] when `T` is [`Send`] and [`Sync`], as readers on different threads share the same value.
+/// Values with their own synchronization, e.g. atomics or a [`Mutex`](std::sync::Mutex), can therefore be mutated
+/// through a snapshot. Such mutations are not replaces: they are visible to all readers of the same value,
+/// but are not carried over to values replacing it, so they are lost unless the writer copies them over.
+///
+/// ```
+/// # use std::sync::{atomic::{AtomicU64, Ordering}, Arc};
+/// # use arcu::{epoch_counters::EpochCounter, Rcu};
+/// let epoch_counters = [Arc::new(EpochCounter::new()), Arc::new(EpochCounter::new())];
+/// let hits = arcu::atomic::Arcu::new(AtomicU64::new(0), epoch_counters.clone());
+///
+/// std::thread::scope(|scope| {
+///     for epoch_counter in &epoch_counters {
+///         let hits = &hits;
+///         // Safety: each thread uses its own epoch counter of the pool
+///         scope.spawn(move || unsafe { hits.raw_read(epoch_counter) }.fetch_add(1, Ordering::Relaxed));
+///     }
+/// });
+///
+/// let old = hits.replace(AtomicU64::new(0));
+/// assert_eq!(old.load(Ordering::Relaxed), 2);
+/// ```
+pub struct Arcu<T: ?Sized + ThinPtr, P, A: ArcLike<Target = T> = Arc<T>> {
     // Safety invariant
-    // - the pointer has been created with Arc::into_raw
-    // - Arcu "owns" one strong reference count
-    active_value: AtomicPtr<T>,
+    // - the pointer has been created with T::into_thin from a pointer created with A::into_raw
+    // - Arcu "owns" the thin pointer and one strong reference count
+    active_value: AtomicPtr<()>,
+    // the number of writes that changed active_value
+    #[cfg(target_has_atomic = "64")]
+    generation: AtomicU64,
+    // the number of writes currently in the process of changing active_value and generation
+    #[cfg(target_has_atomic = "64")]
+    active_writers: AtomicUsize,
+    // whether raw_try_update uses the weak compare exchange
+    weak_updates: bool,
+    #[cfg(feature = "stats")]
+    update_counters: UpdateCounters,
     epoch_counter_pool: P,
-    phantom: PhantomData<Arc<T>>,
+    phantom: PhantomData<A>,
 }
 
 #[cfg(feature = "thread_local_counter")]
-impl<T: core::fmt::Display> core::fmt::Display for Arcu<T, GlobalEpochCounterPool> {
+impl<T: core::fmt::Display + ?Sized + ThinPtr> core::fmt::Display
+    for Arcu<T, GlobalEpochCounterPool>
+{
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         let data = self.read();
         core::fmt::Display::fmt(&data.deref(), f)
     }
 }
 
-impl<T: core::fmt::Debug, P> core::fmt::Debug for Arcu<T, P> {
+/// Compares the current value, see [`Arcu::value_eq`]
+#[cfg(feature = "thread_local_counter")]
+impl<T: PartialEq> PartialEq<T> for Arcu<T, GlobalEpochCounterPool> {
+    fn eq(&self, other: &T) -> bool {
+        self.value_eq(other)
+    }
+}
+
+impl<T: core::fmt::Debug + ?Sized + ThinPtr, P, A: ArcLike<Target = T>> core::fmt::Debug
+    for Arcu<T, P, A>
+{
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.debug_struct("Rcu")
             .field("active_value", &"Opaque")
@@ -43,49 +112,65 @@ impl<T: core::fmt::Debug, P> core::fmt::Debug for Arcu<T, P> {
     }
 }
 
+/// Serializes the current value transparently
+///
+/// The value is read once, like [`Rcu::read`], so the result is a snapshot of a single epoch.
+/// Writes replacing the value while it is serialized are not included.
+#[cfg(all(feature = "serde", feature = "thread_local_counter"))]
+impl<T: ?Sized + ThinPtr + serde::Serialize> serde::Serialize for Arcu<T, GlobalEpochCounterPool> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let value = self.read_arc();
+        T::serialize(&value, serializer)
+    }
+}
+
+/// Deserializes the initial value of a new Arcu with the default pool
+#[cfg(feature = "serde")]
+impl<'de, T: serde::Deserialize<'de>, P: EpochCounterPool + Default> serde::Deserialize<'de>
+    for Arcu<T, P>
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        T::deserialize(deserializer).map(|initial| Self::new(initial, P::default()))
+    }
+}
+
 /// ## Safety
 /// - When mixing safe and unsafe functions care needs to be taken that write operations see all Epochs used by concurrent read operations
 /// - The safe read operations assume that the writer will observe `epoch_counters::THREAD_EPOCH_COUNTER`, see `epoch_counters::with_thread_local_epoch_counter`.
 /// - The safe writers assume that the readers will use one of the epoch counters in `epoch_counters::GLOBAL_EPOCH_COUNTERS`, see `epoch_counters::register_epoch_counter`.
-impl<T, P: EpochCounterPool> Rcu for Arcu<T, P> {
+/// - Raw reads on an Arcu with the [`GlobalEpochCounterPool`](crate::epoch_counters::GlobalEpochCounterPool) can be mixed with safe operations,
+///   as long as their epoch counters have been registered with `epoch_counters::register_epoch_counter` and are not used concurrently.
+impl<T: ?Sized + ThinPtr, P: EpochCounterPool> Rcu for Arcu<T, P> {
     type Item = T;
     type Pool = P;
 
     #[inline]
     fn new(initial: impl Into<Arc<T>>, epoch_counter_pool: P) -> Self {
-        Arcu {
-            active_value: AtomicPtr::new(Arc::into_raw(initial.into()).cast_mut()),
-            epoch_counter_pool,
-            phantom: PhantomData,
-        }
+        Self::from_arc(initial.into(), epoch_counter_pool)
     }
 
     /// ## Safety
     /// - The epoch counter must not be used concurrently
     /// - The epoch counter must be made available to write operations
-    #[inline]
+    #[cfg_attr(not(feature = "profiling"), inline)]
+    #[cfg_attr(feature = "profiling", inline(never))]
     unsafe fn raw_read(&self, epoch_counter: &EpochCounter) -> Arc<T> {
-        epoch_counter.enter_rcs();
-
-        let arc_ptr = self.active_value.load(Ordering::SeqCst);
-
-        // Safety: See comments inside the block
-        let arc = unsafe {
-            // Safety:
-            // - the ptr was created in Rcu::new or Rcu::replace with Arc::into_raw
-            // - the Rcu is responsible for of the arc's strong references
-            // - the Rcu is alive as this function takes a reference to the Rcu
-            // - replace will wait with decrementing the old values strong count until our epoch counter is even again
-            Arc::increment_strong_count(arc_ptr);
-            // Safety:
-            // - the ptr was created in Rcu::new or Rcu::replace with Arc::into_raw
-            // - we have just ensured an additional strong count by incrementing the count
-            Arc::from_raw(arc_ptr)
-        };
-
-        epoch_counter.leave_rcs();
+        // Safety: the caller upholds the safety requirements
+        unsafe { self.raw_read_arc(epoch_counter) }
+    }
 
-        arc
+    /// ## Safety
+    /// - The epoch counter must not be used concurrently
+    /// - The epoch counter must be made available to write operations
+    #[inline]
+    unsafe fn raw_with_read<R>(
+        &self,
+        epoch_counter: &EpochCounter,
+        fun: impl FnOnce(&T) -> R,
+    ) -> R {
+        // Safety: the caller upholds the safety requirements
+        let session = unsafe { self.enter_read(epoch_counter) };
+        fun(session.load())
     }
 
     /// ## Safety
@@ -93,18 +178,25 @@ impl<T, P: EpochCounterPool> Rcu for Arcu<T, P> {
     /// - the vector may contain more epoch counters than required, i.e. epoch counters that are even and epoch counters in use with this Rcu
     #[inline]
     fn replace(&self, new_value: impl Into<Arc<T>>) -> Arc<T> {
-        let arc_ptr = self.active_value.swap(
-            Arc::into_raw(new_value.into()).cast_mut(),
-            Ordering::Acquire,
-        );
-        self.epoch_counter_pool.wait_for_epochs();
+        self.replace_arc(new_value.into())
+    }
 
+    fn try_replace(
+        &self,
+        new_value: impl Into<Arc<T>>,
+    ) -> Result<Arc<T>, crate::reclaim::ReclaimError<'_, T>> {
         // Safety:
-        // - the ptr was created in Arcu::new or Arcu::replace with Arc::into_raw
-        // - we took the strong count of the Rcu
-        // - we witnessed all threads either with an even epoch count or with a new odd count,
-        //   as such they must have left the critical section at some point
-        unsafe { Arc::from_raw(arc_ptr) }
+        // - the old value is only returned once the epochs have been witnessed,
+        //   otherwise it is handed to a ReclaimError which only drops it once they have been
+        let old_value = unsafe { self.swap_unreclaimed(new_value.into()) };
+        match self.epoch_counter_pool.try_wait_for_epochs() {
+            Ok(()) => Ok(old_value.into_inner()),
+            // Safety:
+            // - the old value has been swapped out, so it is reclaimable once the pool has been waited for
+            Err(cause) => Err(unsafe {
+                crate::reclaim::ReclaimError::new(old_value, &self.epoch_counter_pool, cause)
+            }),
+        }
     }
 
     /// Update the Rcu using the provided update function
@@ -114,64 +206,1147 @@ impl<T, P: EpochCounterPool> Rcu for Arcu<T, P> {
     /// ## Safety
     /// - `epoch_counter` must be valid for `raw_read`
     /// - `get_epoch_counters` must be valid for `raw_replace`
+    #[cfg_attr(feature = "profiling", inline(never))]
     unsafe fn raw_try_update<'a>(
         &self,
         mut update: impl FnMut(&T) -> Option<Arc<T>>,
         epoch_counter: &EpochCounter,
     ) -> Option<Arc<T>> {
+        // Safety: the caller upholds the safety requirements
+        unsafe { self.raw_try_update_indexed(|old, _| update(old), epoch_counter) }
+    }
+
+    /// ## Safety
+    /// - `epoch_counter` must be valid for `raw_read`
+    /// - `get_epoch_counters` must be valid for `raw_replace`
+    #[cfg_attr(feature = "profiling", inline(never))]
+    unsafe fn raw_try_update_indexed(
+        &self,
+        mut update: impl FnMut(&T, usize) -> Option<Arc<T>>,
+        epoch_counter: &EpochCounter,
+    ) -> Option<Arc<T>> {
+        let mut failed = 0;
         loop {
             let old = self.raw_read(epoch_counter);
 
-            let new = Arc::into_raw(update(&old)?);
+            let new = update(&old, failed)?;
 
             // we now exchange the ownership of rcu(old) for rcu(new)
             // if rcu(?) is rcu(old)
-            let result = self.active_value.compare_exchange_weak(
-                Arc::as_ptr(&old).cast_mut(),
-                new.cast_mut(),
-                Ordering::AcqRel,
-                Ordering::Relaxed,
-            );
-
+            let result = {
+                // boxed thin pointers are loaded from their box to be compared, see compare_exchange_unreclaimed
+                let _rcs = T::BOXED.then(|| epoch_counter.enter_rcs_guarded());
+                // Safety:
+                // - the old value is only returned once the epochs have been witnessed
+                // - boxed thin pointers are compared within the read critical section
+                unsafe { self.compare_exchange_unreclaimed(&old, new, self.weak_updates) }
+                    // Safety: boxed thin pointers are compared within the read critical section
+                    .map_err(|_new| unsafe { self.is_active(&old) })
+            };
             match result {
                 Ok(old) => {
                     // Compare Exchange Succeeded, ensure the old Arc gets dropped after waiting for all readers to leave the read critical section
+                    self.epoch_counter_pool.wait_for_epochs();
+                    return Some(old.into_inner());
+                }
+                Err(still_active) => {
+                    // Compare Exchange failed, we got back the new arc and drop it before retrying
+                    // it only failed spuriously when the old value is still active
+                    if !still_active {
+                        failed += 1;
+                    }
+                    continue;
+                }
+            }
+        }
+    }
+}
 
-                    // we exchanged the old/new arc pointer
-                    // we are now responsible for one strong count of old,
-                    // in exchange for giving the rcu the responsibility of one strong count of new
+impl<T: ?Sized + ThinPtr, P, A: ArcLike<Target = T>> Arcu<T, P, A> {
+    /// Create a new Arcu with the given initial value and epoch counter pool
+    ///
+    /// This is [`Rcu::new`] for any [`ArcLike`]
+    #[inline]
+    pub fn from_arc(initial: A, epoch_counter_pool: P) -> Self {
+        Arcu {
+            active_value: AtomicPtr::new(T::into_thin(A::into_raw(initial))),
+            #[cfg(target_has_atomic = "64")]
+            generation: AtomicU64::new(0),
+            #[cfg(target_has_atomic = "64")]
+            active_writers: AtomicUsize::new(0),
+            weak_updates: true,
+            #[cfg(feature = "stats")]
+            update_counters: UpdateCounters::default(),
+            epoch_counter_pool,
+            phantom: PhantomData,
+        }
+    }
 
-                    self.epoch_counter_pool.wait_for_epochs();
+    /// Create a new Arcu starting at the given generation, e.g. to continue a persisted generation
+    ///
+    /// See [`Arcu::raw_read_with_generation`]
+    #[cfg(target_has_atomic = "64")]
+    #[inline]
+    pub fn from_arc_with_generation(initial: A, generation: u64, epoch_counter_pool: P) -> Self {
+        let mut arcu = Self::from_arc(initial, epoch_counter_pool);
+        *arcu.generation.get_mut() = generation;
+        arcu
+    }
+
+    /// Choose whether [`Rcu::try_update`] and [`Rcu::raw_try_update`] use a weak compare exchange, the default
+    ///
+    /// A weak compare exchange may fail spuriously, re-running the update function,
+    /// but can be cheaper per attempt on some platforms.
+    /// For expensive update functions the strong compare exchange avoids these re-runs,
+    /// which then only happen when the value actually changed in between.
+    pub fn with_weak_updates(mut self, weak: bool) -> Self {
+        self.weak_updates = weak;
+        self
+    }
+
+    /// The number of compare exchanges and their failures so far, see [`UpdateStats`]
+    ///
+    /// This counts the compare exchanges of [`Rcu::try_update`], [`Rcu::raw_try_update`] and [`Arcu::compare_exchange_arc`],
+    /// e.g. to decide whether to use [`Arcu::with_weak_updates`].
+    #[cfg(feature = "stats")]
+    pub fn update_stats(&self) -> UpdateStats {
+        self.update_counters.stats()
+    }
+
+    /// Consume the Arcu, returning the active value
+    ///
+    /// As the Arcu is owned there can't be any readers, so this doesn't wait for the epochs.
+    pub fn into_inner(self) -> A {
+        let this = core::mem::ManuallyDrop::new(self);
+
+        // Safety:
+        // - the Arcu is never used or dropped again, so the pool is only dropped once
+        drop(unsafe { core::ptr::read(&this.epoch_counter_pool) });
+
+        // Safety:
+        // - the ptr was created in Arcu::from_arc or Arcu::replace_arc with T::into_thin and A::into_raw
+        // - we take over the thin pointer and the strong count of the Arcu, as it won't be dropped
+        // - there can't be readers as we own the Arcu
+        unsafe { A::from_raw(T::from_thin(this.active_value.load(Ordering::Acquire))) }
+    }
+}
+
+impl<T: ?Sized + ThinPtr, P> Arcu<T, P> {
+    /// Get mutable access to the active value, if there are no other references to it
+    ///
+    /// Like [`Arc::get_mut`] this returns `None` while snapshots or other Arcs of the value exist.
+    /// As the Arcu is borrowed mutably there can't be concurrent readers or writers.
+    pub fn get_mut(&mut self) -> Option<&mut T> {
+        // Safety:
+        // - the ptr was created in Arcu::from_arc or Arcu::replace_arc with T::into_thin and Arc::into_raw
+        // - the thin pointer stays owned by the Arcu, so it is only loaded from
+        // - the strong count stays owned by the Arcu, as the Arc is never dropped
+        let mut arc = core::mem::ManuallyDrop::new(unsafe {
+            Arc::from_raw(T::load_thin(*self.active_value.get_mut()))
+        });
+        let value: *mut T = Arc::get_mut(&mut arc)?;
+        // Safety:
+        // - the Arcu holds the only reference to the value, which stays alive while self is borrowed mutably
+        // - the value can only be replaced or read through self, which is borrowed mutably for the returned lifetime
+        Some(unsafe { &mut *value })
+    }
+}
+
+/// Statistics about the compare exchanges of an Arcu, see [`Arcu::update_stats`]
+#[cfg(feature = "stats")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct UpdateStats {
+    /// The number of compare exchanges
+    pub attempts: u64,
+    /// The number of compare exchanges that failed
+    pub failures: u64,
+    /// The number of failures where the active value still was the expected one, i.e. spurious failures
+    ///
+    /// This is an estimate, as the active value may have been replaced and changed back in between.
+    /// Only the weak compare exchange fails spuriously, a high rate under low contention suggests
+    /// disabling it with [`Arcu::with_weak_updates`].
+    pub spurious_estimate: u64,
+}
+
+#[cfg(feature = "stats")]
+#[derive(Default)]
+struct UpdateCounters {
+    attempts: AtomicUsize,
+    failures: AtomicUsize,
+    spurious: AtomicUsize,
+}
+
+#[cfg(feature = "stats")]
+impl UpdateCounters {
+    fn record<T>(&self, result: &Result<*mut T, *mut T>, expected: *mut T) {
+        self.attempts.fetch_add(1, Ordering::Relaxed);
+        if let Err(actual) = result {
+            self.failures.fetch_add(1, Ordering::Relaxed);
+            if *actual == expected {
+                self.spurious.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    fn stats(&self) -> UpdateStats {
+        UpdateStats {
+            attempts: self.attempts.load(Ordering::Relaxed) as u64,
+            failures: self.failures.load(Ordering::Relaxed) as u64,
+            spurious_estimate: self.spurious.load(Ordering::Relaxed) as u64,
+        }
+    }
+}
+
+impl<T: ?Sized + ThinPtr, P> From<Arcu<T, P>> for Arc<T> {
+    /// Consume the Arcu, returning the active value
+    #[inline]
+    fn from(rcu: Arcu<T, P>) -> Self {
+        rcu.into_inner()
+    }
+}
+
+impl<T: ?Sized + ThinPtr, P: EpochCounterPool, A: ArcLike<Target = T>> Arcu<T, P, A> {
+    /// Read the value of the Rcu for the current epoch
+    ///
+    /// This is [`Rcu::raw_read`] for any [`ArcLike`]
+    ///
+    /// ## Safety
+    /// - The epoch counter must not be used concurrently
+    /// - The epoch counter must belong to the EpochCounterPool of this Rcu
+    #[cfg_attr(not(feature = "profiling"), inline)]
+    #[cfg_attr(feature = "profiling", inline(never))]
+    pub unsafe fn raw_read_arc(&self, epoch_counter: &EpochCounter) -> A {
+        self.debug_assert_epoch_counter(epoch_counter);
+        epoch_counter.enter_rcs();
+
+        let thin_ptr = self.active_value.load(Ordering::SeqCst);
+
+        // Safety: See comments inside the block
+        let arc = unsafe {
+            // Safety:
+            // - the ptr was created in Arcu::from_arc or Arcu::replace_arc with T::into_thin and A::into_raw
+            // - the Rcu is responsible for of the arc's strong references and the thin pointer
+            // - the Rcu is alive as this function takes a reference to the Rcu
+            // - replace will wait with decrementing the old values strong count
+            //   and freeing the thin pointer until our epoch counter is even again
+            let arc_ptr = T::load_thin(thin_ptr);
+            A::increment_strong_count(arc_ptr);
+            // Safety:
+            // - the ptr was created in Arcu::from_arc or Arcu::replace_arc with A::into_raw
+            // - we have just ensured an additional strong count by incrementing the count
+            A::from_raw(arc_ptr)
+        };
+
+        epoch_counter.leave_rcs();
+
+        arc
+    }
+
+    /// Replace the Rcu's content with a new value
+    ///
+    /// This is [`Rcu::replace`] for any [`ArcLike`]
+    #[inline]
+    pub fn replace_arc(&self, new_value: A) -> A {
+        let new_value = T::into_thin(A::into_raw(new_value));
+
+        self.begin_write();
+        let arc_ptr = self.active_value.swap(new_value, Ordering::SeqCst);
+        self.end_write(true);
+
+        // A strong count of one can't skip this: a reader may have loaded the pointer without having incremented
+        // the strong count yet, only the epoch counters cover that window. With the epochs checked anyway
+        // the count adds nothing, and the epoch check already finishes after one pass when no reader is active.
+        self.epoch_counter_pool.wait_for_epochs();
+
+        // Safety:
+        // - the ptr was created in Arcu::from_arc or Arcu::replace_arc with T::into_thin and A::into_raw
+        // - we took the thin pointer and the strong count of the Rcu
+        // - we witnessed all threads either with an even epoch count or with a new odd count,
+        //   as such they must have left the critical section at some point
+        unsafe { A::from_raw(T::from_thin(arc_ptr)) }
+    }
+
+    /// Whether `current` is still the active value
+    ///
+    /// ## Safety
+    /// - for values with boxed thin pointers the caller must be in the read critical section of an epoch counter of the pool
+    unsafe fn is_active(&self, current: &A) -> bool {
+        let active = self.active_value.load(Ordering::SeqCst);
+        // Safety:
+        // - the caller is in the read critical section for boxed thin pointers, so the box can't be freed in the meantime
+        // - current is kept alive by the caller, so its address can't be reused by a different allocation in the meantime
+        core::ptr::addr_eq(unsafe { T::load_thin(active) }, A::as_ptr(current))
+    }
+
+    /// Replace the active value with `new` if the active value is still `current`, without waiting for the epochs
+    ///
+    /// Boxed thin pointers, see [`ThinPtr`], can't be compared without loading the active pointer from its box,
+    /// which readers may only do while in their read critical section.
+    ///
+    /// ## Safety
+    /// - the returned old value must not be dropped or handed out before the epochs have been witnessed
+    /// - for values with boxed thin pointers the caller must be in the read critical section of an epoch counter of the pool
+    unsafe fn compare_exchange_unreclaimed(
+        &self,
+        current: &A,
+        new: A,
+        weak: bool,
+    ) -> Result<Unreclaimed<A>, A> {
+        let current = if T::BOXED {
+            let active = self.active_value.load(Ordering::SeqCst);
+            // Safety:
+            // - the caller is in the read critical section, so the box of the active value can't be freed in the meantime
+            //   and its address can't be reused by a different box
+            if !core::ptr::addr_eq(unsafe { T::load_thin(active) }, A::as_ptr(current)) {
+                #[cfg(feature = "stats")]
+                self.update_counters
+                    .record(&Err(active), core::ptr::null_mut());
+                return Err(new);
+            }
+            active
+        } else {
+            // current is kept alive by the caller, so its address can't be reused by a different allocation in the meantime
+            T::into_thin(A::as_ptr(current))
+        };
+        let new = T::into_thin(A::into_raw(new));
 
+        self.begin_write();
+        let result = if weak {
+            self.active_value.compare_exchange_weak(
+                current,
+                new,
+                Ordering::SeqCst,
+                Ordering::Relaxed,
+            )
+        } else {
+            self.active_value
+                .compare_exchange(current, new, Ordering::SeqCst, Ordering::Relaxed)
+        };
+        self.end_write(result.is_ok());
+
+        #[cfg(feature = "stats")]
+        self.update_counters.record(&result, current);
+
+        match result {
+            Ok(old) => {
+                // we exchanged the old/new arc pointer
+                // we are now responsible for one strong count of old,
+                // in exchange for giving the rcu the responsibility of one strong count of new
+
+                // Safety:
+                // - the ptr was created in Arcu::from_arc, Arcu::replace_arc, Arcu::compare_exchange_arc with T::into_thin and A::into_raw
+                // - we took the thin pointer and the strong count of the Arcu
+                // - the caller only drops or hands out the arc once the epochs have been witnessed
+                Ok(unsafe { Unreclaimed::from_thin(old) })
+            }
+            Err(_actual) => {
+                // Compare Exchange failed, reclaim the new arc we leaked with Arc::into_raw above
+
+                // Safety:
+                // - the ptr was just created using T::into_thin and A::into_raw
+                // - we haven't exchanged the references so we are still responsible for the thin pointer and the strong count of new
+                Err(unsafe { A::from_raw(T::from_thin(new)) })
+            }
+        }
+    }
+
+    /// Read the value of the Rcu together with the generation it was written in
+    ///
+    /// The generation starts at zero and is incremented by every write that changes the value,
+    /// the returned value is the one installed by the write that advanced the generation to the returned generation.
+    /// The generation wraps around on overflow, use [`generation_is_newer`] to order generations.
+    ///
+    /// ## Consistency
+    /// The generation works like the sequence number of a seqlock: writers mark themselves as active,
+    /// swap the pointer and advance the generation before leaving, while the read retries
+    /// until no write was active and the generation was stable around loading the pointer.
+    /// Unlike a seqlock's odd sequence number, counting the active writers also covers concurrent writers.
+    /// So a read torn by a concurrent write is retried, until a consistent pair has been observed.
+    ///
+    /// Metadata that must stay consistent with the value, e.g. a sequence number,
+    /// can be derived from the generation or stored in the value itself, instead of in a separate atomic.
+    /// When the writer needs to know the generation it wrote, see [`crate::seq::SeqArcu`].
+    ///
+    /// The generation is only tracked on targets with 64-bit atomics.
+    ///
+    /// ## Safety
+    /// - The epoch counter must not be used concurrently
+    /// - The epoch counter must belong to the EpochCounterPool of this Rcu
+    #[cfg(target_has_atomic = "64")]
+    pub unsafe fn raw_read_with_generation(&self, epoch_counter: &EpochCounter) -> (A, u64) {
+        self.debug_assert_epoch_counter(epoch_counter);
+        loop {
+            epoch_counter.enter_rcs();
+
+            let generation = self.generation.load(Ordering::SeqCst);
+            let thin_ptr = self.active_value.load(Ordering::SeqCst);
+
+            // any write that changed the pointer since we loaded the generation
+            // is either still active or has already advanced the generation
+            let consistent = self.active_writers.load(Ordering::SeqCst) == 0
+                && self.generation.load(Ordering::SeqCst) == generation;
+
+            if consistent {
+                // Safety: See comments inside the block
+                let arc = unsafe {
+                    // Safety:
+                    // - the ptr was created in Arcu::from_arc or Arcu::replace_arc with T::into_thin and A::into_raw
+                    // - the Rcu is responsible for of the arc's strong references and the thin pointer
+                    // - the Rcu is alive as this function takes a reference to the Rcu
+                    // - replace will wait with decrementing the old values strong count
+                    //   and freeing the thin pointer until our epoch counter is even again
+                    let arc_ptr = T::load_thin(thin_ptr);
+                    A::increment_strong_count(arc_ptr);
                     // Safety:
-                    // - the ptr was created in Arcu::new, Arcu::raw_replace, Arcu::raw_try_update with Arc::into_raw
-                    // - we took the strong count of the Arcu
-                    // - we witnessed all threads either with an even epoch count or with a new odd count,
-                    //   as such they must have left the critical section at some point
-                    return Some(unsafe { Arc::from_raw(old) });
+                    // - the ptr was created in Arcu::from_arc or Arcu::replace_arc with A::into_raw
+                    // - we have just ensured an additional strong count by incrementing the count
+                    A::from_raw(arc_ptr)
+                };
+
+                epoch_counter.leave_rcs();
+
+                return (arc, generation);
+            }
+
+            epoch_counter.leave_rcs();
+            core::hint::spin_loop();
+            crate::epoch_counters::busy_wait_hint();
+        }
+    }
+
+    /// Enter the read critical section using the provided epoch counter
+    ///
+    /// The returned session can be used to load the value multiple times,
+    /// all loaded values stay valid until the session is dropped, which leaves the read critical section.
+    /// Writers will block until then, so the session should be kept short.
+    ///
+    /// ## Safety
+    /// - The epoch counter must not be used concurrently
+    /// - The epoch counter must belong to the EpochCounterPool of this Rcu
+    #[inline]
+    pub unsafe fn enter_read<'a>(
+        &'a self,
+        epoch_counter: &'a EpochCounter,
+    ) -> ReadSession<'a, T, P, A> {
+        self.debug_assert_epoch_counter(epoch_counter);
+        ReadSession {
+            arcu: self,
+            _rcs: epoch_counter.enter_rcs_guarded(),
+        }
+    }
+
+    /// Panic in debug builds when the epoch counter doesn't belong to the pool of this Arcu
+    #[inline]
+    fn debug_assert_epoch_counter(&self, epoch_counter: &EpochCounter) {
+        debug_assert_ne!(
+            self.epoch_counter_pool
+                .contains_epoch_counter(epoch_counter),
+            Some(false),
+            "The epoch counter does not belong to the EpochCounterPool of this Arcu!"
+        );
+    }
+
+    /// The generation of the active value, see [`Arcu::raw_read_with_generation`]
+    ///
+    /// This is cheaper than reading the value, e.g. to check whether a value derived from an earlier read is outdated:
+    /// the generation only changes when a write changed the value, and is monotonic up to wrapping around, see [`generation_is_newer`].
+    /// A write may be visible to readers shortly before it advances the generation,
+    /// so record the generation before reading to never miss a change.
+    #[cfg(target_has_atomic = "64")]
+    #[inline]
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::SeqCst)
+    }
+
+    /// The thin pointer to the active value, only to be compared against and never dereferenced
+    #[cfg(all(feature = "thread_local_counter", target_has_atomic = "64"))]
+    pub(crate) fn active_ptr(&self) -> *const () {
+        self.active_value.load(Ordering::SeqCst)
+    }
+
+    /// Mark the start of a write, see [`Arcu::raw_read_with_generation`]
+    #[inline]
+    fn begin_write(&self) {
+        #[cfg(target_has_atomic = "64")]
+        self.active_writers.fetch_add(1, Ordering::SeqCst);
+    }
+
+    /// Mark the end of a write, advancing the generation when the write changed the value
+    #[inline]
+    fn end_write(&self, changed: bool) {
+        #[cfg(target_has_atomic = "64")]
+        {
+            if changed {
+                self.generation.fetch_add(1, Ordering::SeqCst);
+            }
+            self.active_writers.fetch_sub(1, Ordering::SeqCst);
+        }
+        #[cfg(not(target_has_atomic = "64"))]
+        let _ = changed;
+    }
+}
+
+impl<T, P: EpochCounterPool, A: ArcLike<Target = T>> Arcu<T, P, A> {
+    /// Replace the active value with `new` if the active value is still `current`
+    ///
+    /// On success the previous value is returned,
+    /// this will block until the previous value can be reclaimed just like [`Rcu::replace`].
+    /// On failure `new` is handed back unchanged.
+    ///
+    /// ## Weak or Strong
+    /// With `weak` set this uses [`AtomicPtr::compare_exchange_weak`],
+    /// which may fail spuriously even when the active value is `current`, but can be cheaper on some platforms.
+    /// Use it when already retrying in a loop, e.g. when re-reading and recomputing `new` after a failure.
+    ///
+    /// Otherwise this uses [`AtomicPtr::compare_exchange`], which only fails when the active value is not `current`.
+    /// Use it for single attempts, where a spurious failure would be mistaken for a concurrent update.
+    pub fn compare_exchange_arc(&self, current: &A, new: A, weak: bool) -> Result<A, A> {
+        // Safety:
+        // - the old value is only returned once the epochs have been witnessed
+        // - sized values don't have boxed thin pointers
+        let old = unsafe { self.compare_exchange_unreclaimed(current, new, weak) }?;
+
+        // Compare Exchange Succeeded, ensure the old Arc gets dropped after waiting for all readers to leave the read critical section
+        self.epoch_counter_pool.wait_for_epochs();
+
+        Ok(old.into_inner())
+    }
+}
+
+#[cfg(feature = "crossbeam-epoch")]
+impl<T: ?Sized + ThinPtr, A: ArcLike<Target = T>>
+    Arcu<T, crate::epoch_counters::CrossbeamEpochPool, A>
+{
+    /// Read the value of the Rcu while the current thread is pinned by `guard`
+    ///
+    /// See [`CrossbeamEpochPool`](crate::epoch_counters::CrossbeamEpochPool).
+    /// The unprotected guard of `crossbeam_epoch` doesn't pin the thread,
+    /// so it must only be used while there are no concurrent writes.
+    #[inline]
+    pub fn read_pinned(&self, _guard: &crossbeam_epoch::Guard) -> A {
+        // the guard ensures the current thread is pinned while we load the pointer
+        let thin_ptr = self.active_value.load(Ordering::SeqCst);
+
+        // Safety: See comments inside the block
+        unsafe {
+            // Safety:
+            // - the ptr was created in Arcu::from_arc or Arcu::replace_arc with T::into_thin and A::into_raw
+            // - the Rcu is responsible for of the arc's strong references and the thin pointer
+            // - the Rcu is alive as this function takes a reference to the Rcu
+            // - replace will wait with decrementing the old values strong count
+            //   and freeing the thin pointer until the current thread has been unpinned
+            let arc_ptr = T::load_thin(thin_ptr);
+            A::increment_strong_count(arc_ptr);
+            // Safety:
+            // - the ptr was created in Arcu::from_arc or Arcu::replace_arc with A::into_raw
+            // - we have just ensured an additional strong count by incrementing the count
+            A::from_raw(arc_ptr)
+        }
+    }
+}
+
+#[cfg(feature = "thread_local_counter")]
+impl<T: ?Sized + ThinPtr, A: ArcLike<Target = T>> Arcu<T, GlobalEpochCounterPool, A> {
+    /// Read the value of the Rcu for the current epoch
+    ///
+    /// This is [`Rcu::read`] for any [`ArcLike`]
+    pub fn read_arc(&self) -> A {
+        crate::epoch_counters::with_thread_local_epoch_counter(|epoch_counter| {
+            // Safety:
+            // - we just registered the epoch counter
+            // - this is a thread local epoch counter that is only used here, so there can't be a concurrent use
+            unsafe { self.raw_read_arc(epoch_counter) }
+        })
+    }
+}
+
+/// Creates an independent Arcu starting with the current value
+///
+/// The clone shares the current value, but replacing the value of either doesn't affect the other.
+#[cfg(feature = "thread_local_counter")]
+impl<T: ?Sized + ThinPtr, A: ArcLike<Target = T>> Clone for Arcu<T, GlobalEpochCounterPool, A> {
+    fn clone(&self) -> Self {
+        Self::from_arc(self.read_arc(), GlobalEpochCounterPool).with_weak_updates(self.weak_updates)
+    }
+}
+
+/// Creates an independent Arcu starting with the current value, sharing the epoch counter pool
+///
+/// The clone shares the current value, but replacing the value of either doesn't affect the other.
+#[cfg(feature = "thread_local_counter")]
+impl<T: ?Sized + ThinPtr, A: ArcLike<Target = T>> Clone
+    for Arcu<T, crate::epoch_counters::LocalEpochCounterPool, A>
+{
+    fn clone(&self) -> Self {
+        let arc = self
+            .epoch_counter_pool
+            .with_thread_local_epoch_counter(|epoch_counter| {
+                // Safety:
+                // - we just registered the epoch counter with the pool of this Rcu
+                // - this is a thread local epoch counter that is only used here, so there can't be a concurrent use
+                unsafe { self.raw_read_arc(epoch_counter) }
+            });
+        Self::from_arc(arc, self.epoch_counter_pool.clone()).with_weak_updates(self.weak_updates)
+    }
+}
+
+#[cfg(feature = "thread_local_counter")]
+impl<T> Arcu<T, GlobalEpochCounterPool> {
+    /// The strong and weak count of the current value, not counting the strong count owned by the Arcu
+    ///
+    /// The strong count is the number of snapshots currently held by readers, including projections via [`RcuRef`],
+    /// the weak count the number of weak references to the current value.
+    /// The counts are only a snapshot, concurrent reads and writes may change them right after they have been loaded.
+    /// As such they are meant for monitoring, e.g. to find readers keeping old values alive.
+    pub fn active_counts(&self) -> (usize, usize) {
+        crate::epoch_counters::with_thread_local_epoch_counter(|epoch_counter| {
+            // Safety:
+            // - we just registered the epoch counter
+            // - this is a thread local epoch counter that is only used here, so there can't be a concurrent use
+            let _session = unsafe { self.enter_read(epoch_counter) };
+            let thin_ptr = self.active_value.load(Ordering::SeqCst);
+
+            // Safety:
+            // - the ptr was created in Arcu::from_arc or Arcu::replace_arc with T::into_thin and Arc::into_raw
+            // - the Rcu is responsible for one of the arc's strong references,
+            //   which replace will not give up until our epoch counter is even again
+            // - the arc is never dropped, so we don't give up the strong count of the Rcu
+            let arc =
+                core::mem::ManuallyDrop::new(unsafe { Arc::from_raw(T::load_thin(thin_ptr)) });
+
+            (Arc::strong_count(&arc) - 1, Arc::weak_count(&arc))
+        })
+    }
+
+    /// Compare the current value with `other`
+    ///
+    /// This compares a snapshot, a concurrent write may change the value right after the comparison.
+    /// As such this is meant for tests and single threaded contexts, not for synchronization.
+    pub fn value_eq(&self, other: &T) -> bool
+    where
+        T: PartialEq,
+    {
+        self.with_read_borrowed(|current| current == other)
+    }
+
+    /// Replace the active value with `new` if it is still `expected`, to detect concurrent writers
+    ///
+    /// This is a single compare exchange without retrying, for a writer that expects to be the only one.
+    /// On success the previous value is returned, waiting for its epoch like [`Rcu::replace`].
+    /// If another writer replaced the value since `expected` was read a [`ReplaceConflict`] is returned instead.
+    pub fn try_replace_exclusive(
+        &self,
+        expected: &Arc<T>,
+        new: impl Into<Arc<T>>,
+    ) -> Result<Arc<T>, ReplaceConflict<T>> {
+        self.compare_exchange_arc(expected, new.into(), false)
+            .map_err(|rejected| ReplaceConflict {
+                current: self.read(),
+                rejected,
+            })
+    }
+
+    /// Fold the items into the current value one at a time, installing each intermediate value
+    ///
+    /// Each step reads the current value, computes the next one with `fold` and installs it with a compare exchange,
+    /// retrying the step with the item when the value was replaced in between.
+    /// Readers may observe each intermediate value, but unlike calling [`Rcu::try_update`] for each item
+    /// the epochs are only waited for once at the end, reclaiming all replaced values at once.
+    ///
+    /// Returns the replaced values in the order they were replaced, once they can be reclaimed.
+    /// These include values installed by concurrent writers in between the steps.
+    pub fn fold_replace<I, F, R>(&self, items: I, mut fold: F) -> Vec<Arc<T>>
+    where
+        I: IntoIterator,
+        F: FnMut(&T, &I::Item) -> R,
+        R: Into<Arc<T>>,
+    {
+        // Safety invariant
+        // - the values must not be dropped or handed out before the epochs have been witnessed
+        struct Replaced<'a, T> {
+            values: Vec<Unreclaimed<Arc<T>>>,
+            epoch_counter_pool: &'a GlobalEpochCounterPool,
+        }
+
+        impl<T> Drop for Replaced<'_, T> {
+            fn drop(&mut self) {
+                // fold panicked, wait before dropping the values replaced so far
+                if !self.values.is_empty() {
+                    self.epoch_counter_pool.wait_for_epochs();
                 }
-                Err(_new_old) => {
-                    // Compare Exchange failed, reclaim the new arc we leaked with Arc::into_raw above
+            }
+        }
 
-                    // Safety:
-                    // - the ptr was just created using Arc::into_raw
-                    // - there still one strong count left
+        let mut replaced = Replaced {
+            values: Vec::new(),
+            epoch_counter_pool: &self.epoch_counter_pool,
+        };
 
-                    // we haven't exchanged the references so we are still responsible to clean up one strong count of new
-                    let _ = unsafe { Arc::from_raw(new) };
+        for item in items {
+            loop {
+                let current = self.read_arc();
+                let new = fold(&current, &item).into();
+                // Safety:
+                // - the old value is kept in replaced, which is only dropped or handed out after waiting for the epochs
+                // - sized values don't have boxed thin pointers
+                match unsafe { self.compare_exchange_unreclaimed(&current, new, self.weak_updates) }
+                {
+                    Ok(old) => {
+                        replaced.values.push(old);
+                        break;
+                    }
+                    Err(_new) => continue,
+                }
+            }
+        }
 
-                    continue;
+        if !replaced.values.is_empty() {
+            self.epoch_counter_pool.wait_for_epochs();
+        }
+
+        core::mem::take(&mut replaced.values)
+            .into_iter()
+            .map(Unreclaimed::into_inner)
+            .collect()
+    }
+
+    /// Read the value of the Rcu together with the generation it was written in
+    ///
+    /// See [`Arcu::raw_read_with_generation`] and [`Rcu::read`]
+    #[cfg(target_has_atomic = "64")]
+    pub fn read_with_generation(&self) -> (RcuRef<T, T>, u64) {
+        let (arc, generation) =
+            crate::epoch_counters::with_thread_local_epoch_counter(|epoch_counter| {
+                // Safety:
+                // - we just registered the epoch counter
+                // - this is a thread local epoch counter that is only used here, so there can't be a concurrent use
+                unsafe { self.raw_read_with_generation(epoch_counter) }
+            });
+
+        (RcuRef::new(arc), generation)
+    }
+}
+
+#[cfg(feature = "thread_local_counter")]
+impl<T> Arcu<T, crate::epoch_counters::LocalEpochCounterPool> {
+    /// Read the value of the Rcu for the current epoch, see [`Rcu::read`]
+    ///
+    /// The initial read on each thread from each pool may block while registering the thread's epoch counter with the pool.
+    pub fn read(&self) -> RcuRef<T, T> {
+        let arc = self
+            .epoch_counter_pool
+            .with_thread_local_epoch_counter(|epoch_counter| {
+                // Safety:
+                // - we just registered the epoch counter with the pool of this Rcu
+                // - this is a thread local epoch counter that is only used here, so there can't be a concurrent use
+                unsafe { self.raw_read(epoch_counter) }
+            });
+
+        RcuRef::new(arc)
+    }
+
+    /// Update the Rcu using the provided update function, see [`Rcu::try_update`]
+    pub fn try_update<F, R>(&self, mut update: F) -> Option<Arc<T>>
+    where
+        F: FnMut(&T) -> Option<R>,
+        R: Into<Arc<T>>,
+    {
+        self.epoch_counter_pool
+            .with_thread_local_epoch_counter(|epoch_counter| {
+                // Safety:
+                // - we just registered the epoch counter with the pool of this Rcu
+                // - this is a thread local epoch counter that is only used here, so there can't be a concurrent use
+                unsafe {
+                    self.raw_try_update(move |old| update(old).map(Into::into), epoch_counter)
                 }
+            })
+    }
+}
+
+/// Whether generation `a` is newer than generation `b`, see [`Arcu::raw_read_with_generation`]
+///
+/// Generations wrap around on overflow, so this compares the wrapping distance between them
+/// instead of the raw values: `a` is newer if it is less than `2^63` generations ahead of `b`.
+#[cfg(target_has_atomic = "64")]
+#[inline]
+pub const fn generation_is_newer(a: u64, b: u64) -> bool {
+    (a.wrapping_sub(b) as i64) > 0
+}
+
+/// The error of [`Arcu::try_replace_exclusive`] when the value was replaced by a different writer
+#[cfg(feature = "thread_local_counter")]
+#[derive(Debug)]
+pub struct ReplaceConflict<T> {
+    /// The value read after the conflict was detected
+    ///
+    /// This may already be newer than the value that caused the conflict
+    pub current: RcuRef<T, T>,
+    /// The value that was not written
+    pub rejected: Arc<T>,
+}
+
+#[cfg(feature = "thread_local_counter")]
+impl<T> core::fmt::Display for ReplaceConflict<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("the value was replaced by a concurrent writer")
+    }
+}
+
+#[cfg(feature = "thread_local_counter")]
+impl<T: core::fmt::Debug> std::error::Error for ReplaceConflict<T> {}
+
+/// Read all Arcus within a single read critical section on the thread local epoch counter
+///
+/// As the critical section spans the whole batch none of the values can be reclaimed while the batch is being read.
+/// This is not an atomic snapshot: writers may still replace values between the individual loads,
+/// so the result can combine values from before and after a concurrent write.
+///
+/// When the same key occurs more than once the last value is kept.
+#[cfg(feature = "thread_local_counter")]
+pub fn read_map_snapshot<'a, K: Eq + core::hash::Hash, T: ?Sized + ThinPtr + 'a>(
+    rcus: impl IntoIterator<Item = (K, &'a Arcu<T, GlobalEpochCounterPool>)>,
+) -> std::collections::HashMap<K, RcuRef<T, T>> {
+    crate::epoch_counters::with_thread_local_epoch_counter(|epoch_counter| {
+        let _rcs = epoch_counter.enter_rcs_guarded();
+
+        rcus.into_iter()
+            .map(|(key, arcu)| {
+                let thin_ptr = arcu.active_value.load(Ordering::SeqCst);
+
+                // Safety: See comments inside the block
+                let arc = unsafe {
+                    // Safety:
+                    // - the ptr was created in Arcu::from_arc or Arcu::replace_arc with T::into_thin and Arc::into_raw
+                    // - the Rcu is responsible for of the arc's strong references and the thin pointer
+                    // - the Rcu is alive as we got a reference to it
+                    // - replace will wait with decrementing the old values strong count and freeing the thin pointer
+                    //   until our epoch counter is even again, which is only the case after the whole batch has been read
+                    let arc_ptr = T::load_thin(thin_ptr);
+                    Arc::increment_strong_count(arc_ptr);
+                    // Safety:
+                    // - the ptr was created in Arcu::from_arc or Arcu::replace_arc with Arc::into_raw
+                    // - we have just ensured an additional strong count by incrementing the count
+                    Arc::from_raw(arc_ptr)
+                };
+
+                (key, RcuRef::new(arc))
+            })
+            .collect()
+    })
+}
+
+/// A read critical section on the thread local epoch counter, for reading multiple Arcus at once
+///
+/// This is the building block for read-mostly structures made of several Arcus, e.g. a trie with Arcu protected nodes.
+/// All values loaded through the guard stay valid until the guard is dropped,
+/// writers to the loaded Arcus block until then, so the guard should be dropped soon.
+///
+/// ## Composition
+/// - only Arcus using the [`GlobalEpochCounterPool`] can be loaded, as the thread local epoch counter belongs to it
+/// - writing to a loaded Arcu on the same thread while the guard is alive deadlocks
+/// - other reads on the same thread, including further guards, are nested in the guard's read critical section
+///
+/// See also [`read_scope`] and [`arcu_read!`](crate::arcu_read)
+#[cfg(feature = "thread_local_counter")]
+pub struct ReadGuard {
+    epoch_counter: Arc<EpochCounter>,
+    // the thread local epoch counter must not be used from a different thread
+    _not_send: PhantomData<*const ()>,
+}
+
+/// Enter a read critical section on the thread local epoch counter, see [`ReadGuard`]
+#[cfg(feature = "thread_local_counter")]
+pub fn read_scope() -> ReadGuard {
+    ReadGuard::new()
+}
+
+#[cfg(feature = "thread_local_counter")]
+impl ReadGuard {
+    /// Enter the read critical section on the thread local epoch counter
+    pub fn new() -> Self {
+        let epoch_counter = crate::epoch_counters::thread_local_epoch_counter();
+        epoch_counter.enter_rcs();
+        Self {
+            epoch_counter,
+            _not_send: PhantomData,
+        }
+    }
+
+    /// Load the current value of the Arcu
+    ///
+    /// Loading the same Arcu again may return a different value when it is written to in between.
+    #[inline]
+    pub fn load<'a, T: ?Sized + ThinPtr>(
+        &'a self,
+        rcu: &'a Arcu<T, GlobalEpochCounterPool>,
+    ) -> &'a T {
+        let thin_ptr = rcu.active_value.load(Ordering::SeqCst);
+
+        // Safety:
+        // - the ptr was created in Arcu::from_arc or Arcu::replace_arc with T::into_thin and Arc::into_raw
+        // - the Rcu is responsible for one of the arc's strong references and the thin pointer
+        // - the Rcu is alive for 'a
+        // - the thread local epoch counter belongs to the GlobalEpochCounterPool
+        // - replace will wait with decrementing the old values strong count and freeing the thin pointer
+        //   until our epoch counter is even again, which is only the case after the guard has been dropped
+        unsafe { &*T::load_thin(thin_ptr) }
+    }
+}
+
+#[cfg(feature = "thread_local_counter")]
+impl Default for ReadGuard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "thread_local_counter")]
+impl Drop for ReadGuard {
+    fn drop(&mut self) {
+        self.epoch_counter.leave_rcs();
+    }
+}
+
+/// Read multiple Arcus within a single read critical section
+///
+/// Each `let` binds a reference to the current value of the Arcu, these are valid within the block.
+/// The read critical section is left when the block is left, including by an early return or panic.
+/// See [`atomic::ReadGuard`](crate::atomic::ReadGuard) for the restrictions within the block.
+///
+/// ```
+/// # #[cfg(feature = "thread_local_counter")] {
+/// use arcu::{atomic::Arcu, epoch_counters::GlobalEpochCounterPool, Rcu};
+///
+/// let width = Arcu::new(3, GlobalEpochCounterPool);
+/// let height = Arcu::new(4, GlobalEpochCounterPool);
+///
+/// let area = arcu::arcu_read!(let w = &width; let h = &height; { w * h });
+/// assert_eq!(area, 12);
+/// # }
+/// ```
+#[cfg(feature = "thread_local_counter")]
+#[macro_export]
+macro_rules! arcu_read {
+    ($(let $name:ident = $rcu:expr;)+ $body:block) => {{
+        let guard = $crate::atomic::ReadGuard::new();
+        $(let $name = guard.load($rcu);)+
+        $body
+    }};
+}
+
+impl<T: ?Sized + ThinPtr, P: EpochCounterPool> Arcu<T, P> {
+    /// Swap in the new value without waiting for the epochs
+    ///
+    /// ## Safety
+    /// - the returned old value must not be dropped or handed out before the epochs have been witnessed,
+    ///   e.g. by an epoch snapshot taken after this returned
+    pub(crate) unsafe fn swap_unreclaimed(&self, new_value: Arc<T>) -> Unreclaimed<Arc<T>> {
+        let new_value = T::into_thin(Arc::into_raw(new_value));
+
+        self.begin_write();
+        let arc_ptr = self.active_value.swap(new_value, Ordering::SeqCst);
+        self.end_write(true);
+
+        // Safety:
+        // - the ptr was created in Arcu::from_arc or Arcu::replace_arc with T::into_thin and Arc::into_raw
+        // - we took the thin pointer and the strong count of the Rcu
+        // - the caller only drops or hands out the arc once the epochs have been witnessed
+        unsafe { Unreclaimed::from_thin(arc_ptr) }
+    }
+
+    /// The epoch counter pool of this Arcu
+    #[cfg(any(feature = "std", target_has_atomic = "64"))]
+    pub(crate) fn epoch_counter_pool(&self) -> &P {
+        &self.epoch_counter_pool
+    }
+
+    /// Replace the Rcu's content with a new value without waiting for the old value to become reclaimable
+    ///
+    /// This is the non-blocking replace, sometimes called `try_replace`.
+    /// The new value is visible to readers immediately,
+    /// the returned guard hands out the old value once it can be reclaimed, see [`Rcu::replace`].
+    /// This moves the wait off the writer's hot path, e.g. to reclaim a batch of replaced values later,
+    /// see [`DeferredReclaim::reclaim`] and [`DeferredReclaim::try_reclaim`].
+    /// With the `async` feature the guard is also a future resolving to the old value.
+    /// If the guard is dropped before that, dropping it blocks until then.
+    ///
+    /// Checking without blocking requires the pool to support [`EpochCounterPool::epoch_snapshot`],
+    /// otherwise checking blocks like [`Rcu::replace`].
+    pub fn replace_reclaimed(&self, new_value: impl Into<Arc<T>>) -> DeferredReclaim<'_, T, P> {
+        // Safety:
+        // - the arc is only dropped or handed out by DeferredReclaim once the epochs have been witnessed
+        let old_value = unsafe { self.swap_unreclaimed(new_value.into()) };
+
+        DeferredReclaim {
+            old_value: Some(old_value),
+            wait: WaitForEpochs::new(&self.epoch_counter_pool),
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl<T: ?Sized + ThinPtr, P: EpochCounterPool> Arcu<T, P> {
+    /// Replace the Rcu's content with a new value, resolving to the old value once it can be reclaimed
+    ///
+    /// This is [`Rcu::replace`] for async code: the new value is swapped in immediately,
+    /// but instead of blocking the executor thread while waiting for readers
+    /// this yields to the executor between checks, see [`Arcu::replace_reclaimed`] and [`WaitForEpochs`].
+    /// Pools without [`EpochCounterPool::epoch_snapshot`] still block while waiting.
+    pub async fn replace_async(&self, new_value: impl Into<Arc<T>>) -> Arc<T> {
+        self.replace_reclaimed(new_value).await
+    }
+
+    /// Await the new value and replace the Rcu's content with it, resolving to the old value once it can be reclaimed
+    ///
+    /// Reads keep returning the old value while `compute` is pending, e.g. while fetching a new configuration.
+    /// The new value is only built once and swapped in atomically, see [`Arcu::replace_reclaimed`].
+    pub async fn replace_async_computed<R: Into<Arc<T>>>(
+        &self,
+        compute: impl core::future::Future<Output = R>,
+    ) -> Arc<T> {
+        let new_value = compute.await;
+        self.replace_async(new_value).await
+    }
+}
+
+/// A guard holding the value replaced by [`Arcu::replace_reclaimed`] until it can be reclaimed
+///
+/// With the `async` feature this is also a future resolving to the old value.
+pub struct DeferredReclaim<'a, T: ?Sized, P: EpochCounterPool> {
+    // Safety invariant
+    // - while Some the value may still be accessed by readers that haven't incremented the strong count yet
+    old_value: Option<Unreclaimed<Arc<T>>>,
+    wait: WaitForEpochs<'a, P>,
+}
+
+impl<T: ?Sized, P: EpochCounterPool> DeferredReclaim<'_, T, P> {
+    fn is_reclaimable(&mut self) -> bool {
+        self.wait.is_reclaimable()
+    }
+
+    /// Block until the old value can be reclaimed and return it
+    ///
+    /// # Panics
+    /// - when the future has already been polled to completion
+    pub fn reclaim(mut self) -> Arc<T> {
+        let mut backoff = AdaptiveBackoff::default();
+        while !self.is_reclaimable() {
+            backoff.backoff();
+        }
+        self.old_value
+            .take()
+            .expect("DeferredReclaim polled after completion")
+            .into_inner()
+    }
+
+    /// Return the old value if it can be reclaimed, otherwise hand back the guard to try again later
+    ///
+    /// # Panics
+    /// - when the future has already been polled to completion
+    pub fn try_reclaim(mut self) -> Result<Arc<T>, Self> {
+        if self.is_reclaimable() {
+            Ok(self
+                .old_value
+                .take()
+                .expect("DeferredReclaim polled after completion")
+                .into_inner())
+        } else {
+            Err(self)
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl<T: ?Sized, P: EpochCounterPool> core::future::Future for DeferredReclaim<'_, T, P> {
+    type Output = Arc<T>;
+
+    fn poll(
+        mut self: core::pin::Pin<&mut Self>,
+        cx: &mut core::task::Context<'_>,
+    ) -> core::task::Poll<Self::Output> {
+        if self.is_reclaimable() {
+            let old_value = self.old_value.take();
+            core::task::Poll::Ready(
+                old_value
+                    .expect("DeferredReclaim polled after completion")
+                    .into_inner(),
+            )
+        } else {
+            // there is nothing to register the waker with, so re-poll
+            cx.waker().wake_by_ref();
+            core::task::Poll::Pending
+        }
+    }
+}
+
+impl<T: ?Sized, P: EpochCounterPool> Drop for DeferredReclaim<'_, T, P> {
+    fn drop(&mut self) {
+        if self.old_value.is_some() {
+            let mut backoff = AdaptiveBackoff::default();
+            while !self.is_reclaimable() {
+                backoff.backoff();
             }
         }
     }
 }
 
-impl<T, P> Drop for Arcu<T, P> {
+/// A read critical section of an [`Arcu`], see [`Arcu::enter_read`]
+///
+/// The read critical section is left when the session is dropped, even when unwinding.
+pub struct ReadSession<'a, T: ?Sized + ThinPtr, P, A: ArcLike<Target = T> = Arc<T>> {
+    arcu: &'a Arcu<T, P, A>,
+    _rcs: RcsGuard<'a>,
+}
+
+impl<T: ?Sized + ThinPtr, P, A: ArcLike<Target = T>> ReadSession<'_, T, P, A> {
+    /// Load the current value of the Arcu
+    ///
+    /// Subsequent loads may return different values when the Arcu is written to in between,
+    /// all of them stay valid for the remainder of the session.
+    #[inline]
+    pub fn load(&self) -> &T {
+        let thin_ptr = self.arcu.active_value.load(Ordering::SeqCst);
+
+        // Safety:
+        // - the ptr was created in Arcu::from_arc or Arcu::replace_arc with T::into_thin and A::into_raw
+        // - the Rcu is responsible for one of the arc's strong references and the thin pointer
+        // - the Rcu is alive as the session borrows it
+        // - replace will wait with decrementing the old values strong count and freeing the thin pointer
+        //   until our epoch counter is even again, which is only the case after the session has been dropped
+        unsafe { &*T::load_thin(thin_ptr) }
+    }
+
+    /// Load the current value of the Arcu as an Arc that outlives the session
+    #[inline]
+    pub fn load_arc(&self) -> A {
+        let thin_ptr = self.arcu.active_value.load(Ordering::SeqCst);
+
+        // Safety: See comments inside the block
+        unsafe {
+            // Safety:
+            // - the ptr was created in Arcu::from_arc or Arcu::replace_arc with T::into_thin and A::into_raw
+            // - the Rcu is responsible for of the arc's strong references and the thin pointer
+            // - the Rcu is alive as the session borrows it
+            // - replace will wait with decrementing the old values strong count
+            //   and freeing the thin pointer until our epoch counter is even again
+            let arc_ptr = T::load_thin(thin_ptr);
+            A::increment_strong_count(arc_ptr);
+            // Safety:
+            // - the ptr was created in Arcu::from_arc or Arcu::replace_arc with A::into_raw
+            // - we have just ensured an additional strong count by incrementing the count
+            A::from_raw(arc_ptr)
+        }
+    }
+}
+
+impl<T: ?Sized + ThinPtr, P, A: ArcLike<Target = T>> Drop for Arcu<T, P, A> {
     fn drop(&mut self) {
+        // There can't be readers, so this doesn't wait for epochs or touch the pool.
+        // This keeps dropping values containing Arcus, e.g. nested Arcus, free of locks.
+
         // Safety:
-        // - The Pointer was created by Arc::into_raw
-        // - The Arcu is responsible for one strong count, so the string count is at least 1
-        unsafe { Arc::from_raw(self.active_value.load(Ordering::Acquire)) };
+        // - The Pointer was created by T::into_thin and A::into_raw
+        // - The Arcu is responsible for the thin pointer and one strong count, so the string count is at least 1
+        unsafe { A::from_raw(T::from_thin(self.active_value.load(Ordering::Acquire))) };
     }
 }
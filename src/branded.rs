@@ -0,0 +1,181 @@
+//! This module contains a safe alternative to the raw API, using branded lifetimes to tie epoch counters to their pool
+//!
+//! The raw API, e.g. [`Rcu::raw_read`], requires the caller to ensure that the epoch counter belongs to the pool of the Rcu
+//! and isn't used concurrently. Within [`with_brand`] the pool and its epoch counters carry a unique invariant lifetime,
+//! the brand, so the type system ensures the former while [`BrandedCounter`] not being [`Sync`] ensures the latter.
+//!
+//! ```
+//! use arcu::{branded::with_brand, Rcu};
+//!
+//! with_brand(|pool| {
+//!     let counter = pool.counter();
+//!     let rcu = arcu::rwlock::Arcu::new(1, pool.clone());
+//!
+//!     assert_eq!(*counter.read(&rcu), 1);
+//!     rcu.replace(2);
+//!     assert_eq!(*counter.read(&rcu), 2);
+//! });
+//! ```
+//!
+//! A counter of a different brand is rejected:
+//!
+//! ```compile_fail
+//! use arcu::{branded::with_brand, Rcu};
+//!
+//! with_brand(|pool| {
+//!     with_brand(|other_pool| {
+//!         let counter = other_pool.counter();
+//!         let rcu = arcu::rwlock::Arcu::new(1, pool.clone());
+//!         counter.read(&rcu);
+//!     });
+//! });
+//! ```
+//!
+//! As is sharing a counter between threads:
+//!
+//! ```compile_fail
+//! use arcu::{branded::with_brand, Rcu};
+//!
+//! with_brand(|pool| {
+//!     let counter = pool.counter();
+//!     let rcu = arcu::rwlock::Arcu::new(1, pool.clone());
+//!     std::thread::scope(|scope| {
+//!         scope.spawn(|| {
+//!             counter.read(&rcu);
+//!         });
+//!         counter.read(&rcu);
+//!     });
+//! });
+//! ```
+
+use alloc::sync::{Arc, Weak};
+use core::{cell::Cell, marker::PhantomData};
+use std::sync::RwLock;
+
+use crate::{
+    epoch_counters::{EpochCounter, EpochCounterPool, EpochSnapshot},
+    rcu_ref::RcuRef,
+    Rcu,
+};
+
+// invariant in 'id, so that different brands can't be unified
+type Brand<'id> = PhantomData<fn(&'id ()) -> &'id ()>;
+
+/// Call the function with a new [`BrandedPool`], whose brand is different from all other brands
+pub fn with_brand<R>(fun: impl for<'id> FnOnce(BrandedPool<'id>) -> R) -> R {
+    fun(BrandedPool {
+        epoch_counters: Arc::new(RwLock::new(Vec::new())),
+        id: crate::epoch_counters::next_pool_id(),
+        brand: PhantomData,
+    })
+}
+
+/// An [`EpochCounterPool`] that is the only pool with its brand `'id`, see [`with_brand`]
+///
+/// Clones share their epoch counters, so that one clone can be passed to each Rcu using the pool.
+#[derive(Clone)]
+pub struct BrandedPool<'id> {
+    // Safety invariant
+    // - there is only one list of epoch counters per brand, shared by all clones
+    epoch_counters: Arc<RwLock<Vec<Weak<EpochCounter>>>>,
+    id: usize,
+    brand: Brand<'id>,
+}
+
+impl<'id> BrandedPool<'id> {
+    /// Create a new epoch counter registered with this pool
+    pub fn counter(&self) -> BrandedCounter<'id> {
+        let epoch_counter = Arc::new(EpochCounter::new());
+
+        let mut epoch_counters = self.epoch_counters.write().unwrap();
+        // drop the entries of dropped counters, so the list doesn't keep growing
+        epoch_counters.retain(|elem| elem.strong_count() > 0);
+        EpochCounter::tag_weak(&Arc::downgrade(&epoch_counter), self.id);
+        epoch_counters.push(Arc::downgrade(&epoch_counter));
+
+        BrandedCounter {
+            epoch_counter,
+            brand: PhantomData,
+            not_sync: PhantomData,
+        }
+    }
+
+    fn epoch_counters(&self) -> Vec<Weak<EpochCounter>> {
+        self.epoch_counters.read().unwrap().clone()
+    }
+}
+
+impl core::fmt::Debug for BrandedPool<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("BrandedPool").finish_non_exhaustive()
+    }
+}
+
+// Safety:
+// - `wait_for_epochs` and `epoch_snapshot` cover all epoch counters created by `BrandedPool::counter`
+unsafe impl EpochCounterPool for BrandedPool<'_> {
+    fn wait_for_epochs(&self) {
+        (|| self.epoch_counters()).wait_for_epochs()
+    }
+
+    fn epoch_snapshot(&self) -> Option<EpochSnapshot> {
+        Some(EpochSnapshot::new(self.epoch_counters()))
+    }
+
+    fn contains_epoch_counter(&self, epoch_counter: &EpochCounter) -> Option<bool> {
+        epoch_counter.in_pool(self.id)
+    }
+}
+
+/// An epoch counter registered with the [`BrandedPool`] of the brand `'id`
+///
+/// A `BrandedCounter` can be moved to another thread, but not shared between threads,
+/// as its epoch counter must not be used concurrently.
+pub struct BrandedCounter<'id> {
+    epoch_counter: Arc<EpochCounter>,
+    brand: Brand<'id>,
+    not_sync: PhantomData<Cell<()>>,
+}
+
+impl<'id> BrandedCounter<'id> {
+    /// Read the value of the Rcu, see [`Rcu::read`]
+    pub fn read<R>(&self, rcu: &R) -> RcuRef<R::Item, R::Item>
+    where
+        R: Rcu<Pool = BrandedPool<'id>>,
+    {
+        // Safety:
+        // - the brand ensures the epoch counter has been registered with the only pool of the brand
+        // - the counter isn't Sync and doesn't give out its epoch counter, so there can't be a concurrent use
+        RcuRef::new(unsafe { rcu.raw_read(&self.epoch_counter) })
+    }
+
+    /// Call the function with a reference to the value of the Rcu, see [`Rcu::with_read_borrowed`]
+    pub fn with_read<R, T>(&self, rcu: &R, fun: impl FnOnce(&R::Item) -> T) -> T
+    where
+        R: Rcu<Pool = BrandedPool<'id>>,
+    {
+        // Safety:
+        // - the brand ensures the epoch counter has been registered with the only pool of the brand
+        // - the counter isn't Sync and doesn't give out its epoch counter, so there can't be a concurrent use
+        unsafe { rcu.raw_with_read(&self.epoch_counter, fun) }
+    }
+
+    /// Update the Rcu using the provided update function, see [`Rcu::try_update`]
+    pub fn try_update<R, F, U>(&self, rcu: &R, mut update: F) -> Option<Arc<R::Item>>
+    where
+        R: Rcu<Pool = BrandedPool<'id>>,
+        F: FnMut(&R::Item) -> Option<U>,
+        U: Into<Arc<R::Item>>,
+    {
+        // Safety:
+        // - the brand ensures the epoch counter has been registered with the only pool of the brand
+        // - the counter isn't Sync and doesn't give out its epoch counter, so there can't be a concurrent use
+        unsafe { rcu.raw_try_update(move |old| update(old).map(Into::into), &self.epoch_counter) }
+    }
+}
+
+impl core::fmt::Debug for BrandedCounter<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("BrandedCounter").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,200 @@
+//! This module contains readers that cache the value last read from an [`Rcu`]
+
+use alloc::sync::Arc;
+use core::cell::RefCell;
+
+#[cfg(all(target_has_atomic = "ptr", target_has_atomic = "64"))]
+use crate::atomic::Arcu;
+use crate::{
+    epoch_counters::{try_with_thread_local_epoch_counter, GlobalEpochCounterPool},
+    rcu_ref::RcuRef,
+    Rcu,
+};
+
+/// A reader that never blocks, falling back to the value it last read
+///
+/// The initial read on each thread may block while registering the thread's epoch counter, see [`Rcu::read`].
+/// For threads that can't tolerate this, e.g. real-time threads,
+/// [`CachingReader::read_fast`] instead returns the cached value and retries on the next read.
+///
+/// ## Staleness
+/// The cached value is only returned while the registration of the thread's epoch counter is contended.
+/// As soon as the registration succeeded, each read returns the current value and refreshes the cache.
+/// As such the returned value is at most as stale as the value read on the last successful read,
+/// or the initial value if no read has succeeded yet.
+///
+/// A `CachingReader` is meant to be used by a single thread, it can be created on a different thread and moved over.
+pub struct CachingReader<T> {
+    cached: RefCell<Arc<T>>,
+}
+
+impl<T> CachingReader<T> {
+    /// Create a new `CachingReader` returning `initial` until the first successful read
+    pub fn new(initial: impl Into<Arc<T>>) -> Self {
+        Self {
+            cached: RefCell::new(initial.into()),
+        }
+    }
+
+    /// Read the value of the Rcu without ever blocking
+    ///
+    /// Returns the current value of the Rcu if the read succeeded without blocking,
+    /// otherwise returns the cached value.
+    pub fn read_fast<R>(&self, rcu: &R) -> RcuRef<T, T>
+    where
+        R: Rcu<Item = T, Pool = GlobalEpochCounterPool>,
+    {
+        let current = try_with_thread_local_epoch_counter(|epoch_counter| {
+            // Safety:
+            // - we just registered the epoch counter
+            // - this is a thread local epoch counter that is only used here, so there can't be a concurrent use
+            unsafe { rcu.raw_read(epoch_counter) }
+        });
+
+        match current {
+            Some(current) => {
+                *self.cached.borrow_mut() = Arc::clone(&current);
+                RcuRef::new(current)
+            }
+            None => RcuRef::new(Arc::clone(&self.cached.borrow())),
+        }
+    }
+}
+
+/// A reader that reuses the value it last read while the [`Arcu`] hasn't been written to
+///
+/// Reading an [`Arcu`] increments the strong count of the active value, which is shared by all threads.
+/// For a hot value that rarely changes, [`CachedReader::read`] instead only compares the active pointer
+/// with the cached one and hands out a reference to the cached [`Arc`],
+/// only reading the Arcu again after it has been written to.
+///
+/// As the cached Arc keeps its allocation alive, a matching pointer always refers to the cached value.
+/// Cloning the returned Arc to get an owned handle increments the shared strong count again.
+///
+/// Keeping the cached value alive delays dropping a replaced value until the next read through the `CachedReader`.
+#[cfg(all(target_has_atomic = "ptr", target_has_atomic = "64"))]
+pub struct CachedReader<T> {
+    cached: Option<Arc<T>>,
+}
+
+#[cfg(all(target_has_atomic = "ptr", target_has_atomic = "64"))]
+impl<T> CachedReader<T> {
+    /// Create a new `CachedReader` with an empty cache
+    pub const fn new() -> Self {
+        Self { cached: None }
+    }
+
+    /// Read the value of the Arcu, reusing the cached value if the Arcu hasn't changed since the last read
+    pub fn read(&mut self, rcu: &Arcu<T, GlobalEpochCounterPool>) -> &Arc<T> {
+        let active = rcu.active_ptr();
+
+        match &mut self.cached {
+            Some(cached) if Arc::as_ptr(cached).cast::<()>() == active => {}
+            cached => *cached = Some(rcu.read_arc()),
+        }
+
+        self.cached.as_ref().expect("the cache was just filled")
+    }
+
+    /// Drop the cached value
+    pub fn clear(&mut self) {
+        self.cached = None;
+    }
+}
+
+#[cfg(all(target_has_atomic = "ptr", target_has_atomic = "64"))]
+impl<T> Default for CachedReader<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A reader that reuses the snapshot it last read while the generation of the [`Arcu`] is unchanged
+///
+/// [`ReaderCache::read_cached`] only loads the generation and the active pointer of the Arcu,
+/// and if both match the cached snapshot returns a clone of it without entering the read critical section.
+/// As generations are counted per Arcu, comparing the pointer keeps a cache used with different Arcus from
+/// returning the value of a different Arcu at the same generation.
+/// The cached snapshot keeps its allocation alive, so a matching pointer always refers to the cached value.
+/// Cloning the cached snapshot still increments the strong count shared by all threads,
+/// use [`CachedReader`] to avoid that when a reference to the cached value is sufficient.
+///
+/// See [`Arcu::read_with_generation`]
+#[cfg(all(target_has_atomic = "ptr", target_has_atomic = "64"))]
+pub struct ReaderCache<T> {
+    cached: Option<(RcuRef<T, T>, u64)>,
+}
+
+#[cfg(all(target_has_atomic = "ptr", target_has_atomic = "64"))]
+impl<T> ReaderCache<T> {
+    /// Create a new `ReaderCache` with an empty cache
+    pub const fn new() -> Self {
+        Self { cached: None }
+    }
+
+    /// Read the value of the Arcu, reusing the cached snapshot if the Arcu hasn't changed
+    pub fn read_cached(&mut self, rcu: &Arcu<T, GlobalEpochCounterPool>) -> RcuRef<T, T> {
+        let generation = rcu.generation();
+        let active = rcu.active_ptr();
+
+        match &self.cached {
+            Some((cached, cached_generation))
+                if *cached_generation == generation
+                    && core::ptr::addr_eq(&**cached as *const T, active) =>
+            {
+                RcuRef::clone(cached)
+            }
+            _ => {
+                let (current, generation) = rcu.read_with_generation();
+                self.cached = Some((RcuRef::clone(&current), generation));
+                current
+            }
+        }
+    }
+
+    /// Drop the cached snapshot
+    pub fn clear(&mut self) {
+        self.cached = None;
+    }
+}
+
+#[cfg(all(target_has_atomic = "ptr", target_has_atomic = "64"))]
+impl<T> Default for ReaderCache<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A cache for repeated reads of one [`Arcu`], like `arc_swap::Cache`
+///
+/// The cache holds on to the Arcu, e.g. as a reference or an [`Arc`], together with a [`CachedReader`].
+/// [`Cache::load`] takes `&mut self`, so each thread needs its own cache,
+/// and the returned value may be outdated until `load` is called again.
+#[cfg(all(target_has_atomic = "ptr", target_has_atomic = "64"))]
+pub struct Cache<R, T> {
+    rcu: R,
+    reader: CachedReader<T>,
+}
+
+#[cfg(all(target_has_atomic = "ptr", target_has_atomic = "64"))]
+impl<R: core::ops::Deref<Target = Arcu<T, GlobalEpochCounterPool>>, T> Cache<R, T> {
+    /// Create a new `Cache` for the Arcu
+    pub const fn new(rcu: R) -> Self {
+        Self {
+            rcu,
+            reader: CachedReader::new(),
+        }
+    }
+
+    /// Get the Arcu of the cache
+    pub fn rcu(&self) -> &R {
+        &self.rcu
+    }
+
+    /// Load the value of the Arcu, reusing the cached value if the Arcu hasn't changed since the last load
+    ///
+    /// See [`CachedReader::read`]
+    pub fn load(&mut self) -> &Arc<T> {
+        self.reader.read(&self.rcu)
+    }
+}
@@ -0,0 +1,136 @@
+//! This module contains [`DeferredReclaimer`], an [`Arcu`] whose writes don't wait for readers
+
+use alloc::{collections::VecDeque, sync::Arc};
+use std::sync::Mutex;
+
+use crate::{
+    atomic::Arcu,
+    epoch_counters::{AdaptiveBackoff, Backoff, EpochCounterPool, EpochSnapshot},
+    reclaim::Unreclaimed,
+    Rcu,
+};
+
+/// An [`Arcu`] that queues replaced values for reclamation instead of waiting for readers
+///
+/// [`DeferredReclaimer::replace_deferred`] swaps in the new value and queues the old one
+/// until all readers that might still access it have left their read critical section.
+/// Queued values are dropped by [`DeferredReclaimer::reclaim`], which each write calls as well.
+///
+/// ## Back-pressure
+/// When writes outpace the readers the queue grows, [`DeferredReclaimer::pending_reclamations`] makes this observable.
+/// With a limit on pending reclamations writes are rejected while the queue is full,
+/// letting the writer slow down or fall back to the blocking [`Rcu::replace`] of [`DeferredReclaimer::arcu`].
+///
+/// Deferring requires the pool to support [`EpochCounterPool::epoch_snapshot`],
+/// otherwise writes wait like [`Rcu::replace`].
+///
+/// ## Reclaiming thread
+/// Values are dropped on the thread calling [`DeferredReclaimer::reclaim`], or on the writing thread by default.
+/// With [`DeferredReclaimer::with_reclaim_on_write`] disabled only `reclaim` drops values, so a dedicated thread
+/// calling it can do all deallocations, e.g. pinned to the NUMA node the values were allocated on.
+/// The DeferredReclaimer doesn't spawn or own any threads, so it doesn't configure their affinity either,
+/// pinning the reclaiming thread is left to the caller, e.g. with the `core_affinity` crate.
+/// Pools without epoch snapshots always drop on the writing thread.
+pub struct DeferredReclaimer<T, P: EpochCounterPool> {
+    arcu: Arcu<T, P>,
+    // Safety invariant
+    // - the values must not be dropped before their snapshot is reclaimable
+    pending: Mutex<VecDeque<(Unreclaimed<Arc<T>>, EpochSnapshot)>>,
+    max_pending: Option<usize>,
+    reclaim_on_write: bool,
+}
+
+impl<T, P: EpochCounterPool> DeferredReclaimer<T, P> {
+    /// Create a new DeferredReclaimer, rejecting writes while `max_pending` values are waiting for reclamation
+    pub fn new(
+        initial: impl Into<Arc<T>>,
+        epoch_counter_pool: P,
+        max_pending: Option<usize>,
+    ) -> Self {
+        Self {
+            arcu: Arcu::new(initial, epoch_counter_pool),
+            pending: Mutex::new(VecDeque::new()),
+            max_pending,
+            reclaim_on_write: true,
+        }
+    }
+
+    /// Choose whether [`DeferredReclaimer::replace_deferred`] reclaims values, the default
+    ///
+    /// When disabled values are only dropped by [`DeferredReclaimer::reclaim`] and when dropping the DeferredReclaimer,
+    /// writes are then rejected once `max_pending` values are waiting, even if they have become reclaimable.
+    pub fn with_reclaim_on_write(mut self, reclaim_on_write: bool) -> Self {
+        self.reclaim_on_write = reclaim_on_write;
+        self
+    }
+
+    /// Get the underlying Arcu, e.g. to read from it
+    pub fn arcu(&self) -> &Arcu<T, P> {
+        &self.arcu
+    }
+
+    /// Replace the value without waiting for the old value to become reclaimable
+    ///
+    /// Returns the new value back when the limit of pending reclamations has been reached,
+    /// even after reclaiming all values that have become reclaimable.
+    pub fn replace_deferred(&self, new_value: impl Into<Arc<T>>) -> Result<(), Arc<T>> {
+        let new_value = new_value.into();
+        let mut pending = self.pending.lock().unwrap();
+
+        if self.reclaim_on_write {
+            Self::reclaim_pending(&mut pending);
+        }
+        if self
+            .max_pending
+            .is_some_and(|max_pending| pending.len() >= max_pending)
+        {
+            return Err(new_value);
+        }
+
+        // Safety:
+        // - the old value is only dropped once the snapshot taken afterwards is reclaimable
+        let old_value = unsafe { self.arcu.swap_unreclaimed(new_value) };
+
+        match self.arcu.epoch_counter_pool().epoch_snapshot() {
+            Some(snapshot) => pending.push_back((old_value, snapshot)),
+            None => {
+                drop(pending);
+                self.arcu.epoch_counter_pool().wait_for_epochs();
+                drop(old_value);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Drop all replaced values that have become reclaimable, returning how many were dropped
+    pub fn reclaim(&self) -> usize {
+        Self::reclaim_pending(&mut self.pending.lock().unwrap())
+    }
+
+    /// The number of replaced values waiting to be reclaimed
+    pub fn pending_reclamations(&self) -> usize {
+        self.pending.lock().unwrap().len()
+    }
+
+    fn reclaim_pending(pending: &mut VecDeque<(Unreclaimed<Arc<T>>, EpochSnapshot)>) -> usize {
+        let before = pending.len();
+        pending.retain_mut(|(_, snapshot)| !snapshot.is_reclaimable());
+        before - pending.len()
+    }
+}
+
+impl<T, P: EpochCounterPool> Drop for DeferredReclaimer<T, P> {
+    fn drop(&mut self) {
+        let pending = self
+            .pending
+            .get_mut()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+
+        let mut backoff = AdaptiveBackoff::default();
+        while !pending.is_empty() {
+            Self::reclaim_pending(pending);
+            backoff.backoff();
+        }
+    }
+}
@@ -1,7 +1,8 @@
 //! This module contains [`EpochCounter`], [`EpochCounterPool`] and related functionality.
 
 use alloc::sync::{Arc, Weak};
-use core::sync::atomic::{AtomicU8, Ordering};
+use core::marker::PhantomData;
+use core::sync::atomic::{AtomicUsize, Ordering};
 
 // the epoch counters of all threads that have ever accessed an Rcu
 // threads that have finished will have a dangling Weak reference and can be cleaned up
@@ -14,9 +15,63 @@ static GLOBAL_EPOCH_COUNTERS: std::sync::RwLock<Vec<alloc::sync::Weak<EpochCount
 
 #[cfg(feature = "global_counters")]
 pub fn register_epoch_counter(epoch_counter: alloc::sync::Weak<EpochCounter>) {
+    EpochCounter::tag_weak(&epoch_counter, GLOBAL_POOL_ID);
     GLOBAL_EPOCH_COUNTERS.write().unwrap().push(epoch_counter)
 }
 
+/// Reserve capacity for at least `additional` more epoch counters in the global counters
+///
+/// Registering an epoch counter, e.g. on the first read of each thread, takes the write lock of the global counters.
+/// Reserving the capacity for the expected number of threads at startup avoids reallocating while holding it.
+#[cfg(feature = "global_counters")]
+pub fn reserve_global_counters(additional: usize) {
+    GLOBAL_EPOCH_COUNTERS.write().unwrap().reserve(additional)
+}
+
+/// The number of epoch counters the global counters can hold without reallocating
+#[cfg(feature = "global_counters")]
+pub fn global_counters_capacity() -> usize {
+    GLOBAL_EPOCH_COUNTERS.read().unwrap().capacity()
+}
+
+#[cfg(feature = "global_counters")]
+pub(crate) fn try_register_epoch_counter(epoch_counter: alloc::sync::Weak<EpochCounter>) -> bool {
+    match GLOBAL_EPOCH_COUNTERS.try_write() {
+        Ok(mut counters) => {
+            EpochCounter::tag_weak(&epoch_counter, GLOBAL_POOL_ID);
+            counters.push(epoch_counter);
+            true
+        }
+        Err(std::sync::TryLockError::WouldBlock) => false,
+        Err(std::sync::TryLockError::Poisoned(err)) => panic!("{err}"),
+    }
+}
+
+/// Remove the epoch counters that have been dropped from the global counters, returning how many were removed
+///
+/// Thread local epoch counters are dropped when their thread exits, but stay in the global counters,
+/// which writers have to go through on every write. Servers spawning many short-lived threads should call this
+/// periodically. Writers already waiting on a copy of the global counters are unaffected, as dropped counters are skipped anyway.
+#[cfg(feature = "global_counters")]
+pub fn prune_global_epoch_counters() -> usize {
+    let mut epoch_counters = GLOBAL_EPOCH_COUNTERS.write().unwrap();
+    let before = epoch_counters.len();
+    epoch_counters.retain(|elem| elem.strong_count() > 0);
+    before - epoch_counters.len()
+}
+
+/// Remove the epoch counter from the global counters, along with all counters that have already been dropped
+///
+/// The epoch counter must not be used afterwards, as writes no longer wait for it.
+#[cfg(feature = "global_counters")]
+pub(crate) fn unregister_epoch_counter(epoch_counter: &EpochCounter) {
+    epoch_counter.untag(GLOBAL_POOL_ID);
+    GLOBAL_EPOCH_COUNTERS
+        .write()
+        .unwrap()
+        .retain(|elem| elem.strong_count() > 0 && !core::ptr::eq(elem.as_ptr(), epoch_counter))
+}
+
 #[cfg(feature = "global_counters")]
 pub fn global_counters() -> Vec<::alloc::sync::Weak<EpochCounter>> {
     GLOBAL_EPOCH_COUNTERS.read().unwrap().clone()
@@ -30,35 +85,127 @@ thread_local! {
     static THREAD_EPOCH_COUNTER: std::cell::OnceCell<std::sync::Arc<EpochCounter>> = const { std::cell::OnceCell::new() };
 }
 
+/// The pool of all epoch counters registered with [`register_epoch_counter`], including the thread local ones
+///
+/// On targets without threads, i.e. `wasm32` without the `atomics` target feature, writes don't wait for readers,
+/// as there can't be readers on other threads. Writing while reading on the only thread panics instead of never finishing.
+/// The replaced value is handed back right away, so it is dropped as soon as the writer drops it.
+///
+/// Reads still enter the read critical section there, unlike writes they have no separate single threaded path.
+/// A write from within a read on the same thread, e.g. from within [`crate::Rcu::with_read_borrowed`],
+/// would otherwise free the value still being borrowed, instead of panicking.
+/// Without threads the atomics of the epoch counter are plain memory accesses, so this only costs a few increments.
 #[cfg(feature = "global_counters")]
+#[derive(Clone, Copy, Default)]
 pub struct GlobalEpochCounterPool;
 
+// Safety:
+// - `wait_for_epochs` waits like the pool of `global_counters`
+// - without threads it only returns once all epoch counters have been witnessed to be even
 #[cfg(feature = "global_counters")]
 unsafe impl EpochCounterPool for GlobalEpochCounterPool {
     fn wait_for_epochs(&self) {
+        #[cfg(all(target_arch = "wasm32", not(target_feature = "atomics")))]
+        assert!(
+            EpochSnapshot::new(global_counters()).is_reclaimable(),
+            "Writing to an Arcu while reading on a target without threads would never finish!"
+        );
+        #[cfg(not(all(target_arch = "wasm32", not(target_feature = "atomics"))))]
         global_counters.wait_for_epochs()
     }
+
+    fn epoch_snapshot(&self) -> Option<EpochSnapshot> {
+        global_counters.epoch_snapshot()
+    }
+
+    fn contains_epoch_counter(&self, epoch_counter: &EpochCounter) -> Option<bool> {
+        // compares the tag instead of locking the global counters, which must not block reads like CachingReader::read_fast
+        epoch_counter.in_pool(GLOBAL_POOL_ID)
+    }
 }
 
 /// Calls the provided function with the thread local epoch counter
 ///
 /// Per Thread: On first use registers the epoch counter
 #[cfg(feature = "thread_local_counter")]
+#[inline]
 pub(crate) fn with_thread_local_epoch_counter<T>(fun: impl FnOnce(&EpochCounter) -> T) -> T {
     THREAD_EPOCH_COUNTER.with(|epoch_counter| {
-        let epoch_counter = epoch_counter.get_or_init(|| {
-            let epoch_counter = Arc::new(EpochCounter::new());
+        let epoch_counter = epoch_counter.get_or_init(new_thread_epoch_counter);
+
+        fun(&epoch_counter)
+    })
+}
 
-            // register the current threads epoch counter on init
-            register_epoch_counter(Arc::downgrade(&epoch_counter));
+/// Get the thread local epoch counter
+///
+/// Per Thread: On first use registers the epoch counter
+#[cfg(all(feature = "thread_local_counter", target_has_atomic = "ptr"))]
+pub(crate) fn thread_local_epoch_counter() -> Arc<EpochCounter> {
+    THREAD_EPOCH_COUNTER
+        .with(|epoch_counter| Arc::clone(epoch_counter.get_or_init(new_thread_epoch_counter)))
+}
 
-            epoch_counter
-        });
+// only called on the first read of each thread, keep it out of the inlined read path
+#[cfg(feature = "thread_local_counter")]
+#[cold]
+#[inline(never)]
+fn new_thread_epoch_counter() -> Arc<EpochCounter> {
+    let epoch_counter = Arc::new(EpochCounter::new());
 
-        fun(&epoch_counter)
+    // register the current threads epoch counter on init
+    register_epoch_counter(Arc::downgrade(&epoch_counter));
+
+    epoch_counter
+}
+
+/// Calls the provided function with the thread local epoch counter
+///
+/// Per Thread: On first use tries to register the epoch counter,
+/// returns `None` without calling the function when registering would block
+#[cfg(feature = "thread_local_counter")]
+pub(crate) fn try_with_thread_local_epoch_counter<T>(
+    fun: impl FnOnce(&EpochCounter) -> T,
+) -> Option<T> {
+    THREAD_EPOCH_COUNTER.with(|epoch_counter| {
+        let epoch_counter = match epoch_counter.get() {
+            Some(epoch_counter) => epoch_counter,
+            None => {
+                let new_counter = Arc::new(EpochCounter::new());
+
+                // only keep the epoch counter when we managed to register it
+                if !try_register_epoch_counter(Arc::downgrade(&new_counter)) {
+                    return None;
+                }
+
+                epoch_counter.get_or_init(|| new_counter)
+            }
+        };
+
+        Some(fun(epoch_counter))
     })
 }
 
+// the pool ids tagging epoch counters, see EpochCounter::in_pool
+#[cfg(feature = "std")]
+const NO_POOL_ID: usize = 0;
+#[cfg(feature = "std")]
+const MULTIPLE_POOLS_ID: usize = usize::MAX;
+#[cfg(feature = "global_counters")]
+const GLOBAL_POOL_ID: usize = 1;
+#[cfg(feature = "std")]
+static NEXT_POOL_ID: AtomicUsize = AtomicUsize::new(2);
+
+/// A new id for a pool tagging the epoch counters registered with it, see [`EpochCounter::in_pool`]
+#[cfg(feature = "std")]
+pub(crate) fn next_pool_id() -> usize {
+    NEXT_POOL_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+#[cfg(target_has_atomic = "ptr")]
+const CONCURRENT_USE: &str =
+    "The epoch counter is in an inconsistent state, it must not be used concurrently by multiple threads!";
+
 /// An epoch counter for Arcu
 ///
 /// This is used to prevent deallocating
@@ -66,39 +213,152 @@ pub(crate) fn with_thread_local_epoch_counter<T>(fun: impl FnOnce(&EpochCounter)
 ///
 /// An even counter values means the EpochCounter is inactive i.e outside the critical section.
 /// An odd counter value means the EpochCounter is active i.e. in the critical section.
-#[repr(transparent)]
-pub struct EpochCounter(core::sync::atomic::AtomicU8);
+///
+/// Entering the read-critical-section again while already in it, e.g. reading from within [`crate::Rcu::with_read_borrowed`],
+/// only increases the nesting depth, the EpochCounter stays active until the outermost read-critical-section is left.
+///
+/// Each EpochCounter is aligned to 64 bytes, so counters stored next to each other never share a cache line of that size.
+///
+/// The counter is as wide as a pointer, so it only wraps around to the same value after `usize::MAX` reads.
+/// A wrap-around while a writer waits only makes the writer wait longer, as it can't tell the reader has moved on.
+#[repr(align(64))]
+pub struct EpochCounter {
+    epoch: AtomicUsize,
+    /// only accessed by the thread using the epoch counter, writers only look at the epoch
+    #[cfg(target_has_atomic = "ptr")]
+    depth: AtomicUsize,
+    /// the id of the pool the epoch counter has been registered with, only used by debug assertions
+    #[cfg(feature = "std")]
+    pool_id: AtomicUsize,
+}
 
 impl EpochCounter {
     /// Create a new EpochCounter
     #[inline]
     pub const fn new() -> Self {
-        Self(AtomicU8::new(0))
+        Self {
+            epoch: AtomicUsize::new(0),
+            #[cfg(target_has_atomic = "ptr")]
+            depth: AtomicUsize::new(0),
+            #[cfg(feature = "std")]
+            pool_id: AtomicUsize::new(NO_POOL_ID),
+        }
+    }
+
+    /// Tag the epoch counter as registered with the pool, unless it has been dropped already
+    #[cfg(feature = "std")]
+    pub(crate) fn tag_weak(epoch_counter: &Weak<EpochCounter>, pool_id: usize) {
+        if let Some(epoch_counter) = epoch_counter.upgrade() {
+            // Relaxed: the tag is only used by debug assertions on threads the epoch counter has been handed to
+            let _ = epoch_counter.pool_id.fetch_update(
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+                |current| match current {
+                    NO_POOL_ID => Some(pool_id),
+                    current if current == pool_id => None,
+                    _ => Some(MULTIPLE_POOLS_ID),
+                },
+            );
+        }
+    }
+
+    /// Remove the tag of the pool, when the epoch counter is no longer registered with it
+    #[cfg(feature = "global_counters")]
+    pub(crate) fn untag(&self, pool_id: usize) {
+        let _ = self.pool_id.compare_exchange(
+            pool_id,
+            NO_POOL_ID,
+            Ordering::Relaxed,
+            Ordering::Relaxed,
+        );
+    }
+
+    /// Whether the epoch counter has been registered with the pool, without going through the pool's epoch counters
+    ///
+    /// Returns `None` for epoch counters registered with multiple pools.
+    #[cfg(feature = "std")]
+    pub(crate) fn in_pool(&self, pool_id: usize) -> Option<bool> {
+        match self.pool_id.load(Ordering::Relaxed) {
+            MULTIPLE_POOLS_ID => None,
+            current => Some(current == pool_id),
+        }
     }
 
-    /// Increment the epoch counter to enter the read-critical-section
+    /// Increment the epoch counter to enter the read-critical-section,
+    /// when nested only increment the depth
     ///
     /// # Panics
-    /// - when the Epoch counter odd i.e. is already active/in the read critical section
+    /// - when the epoch counter is found to be in an inconsistent state, e.g. due to being used concurrently
+    #[cfg(target_has_atomic = "ptr")]
     #[inline]
+    #[track_caller]
     pub(crate) fn enter_rcs(&self) {
-        let old = self.0.fetch_add(1, Ordering::Acquire);
-        assert!(old % 2 == 0, "Old Epoch counter value should be even!");
+        let depth = self.depth.load(Ordering::Relaxed);
+        self.depth.store(depth + 1, Ordering::Relaxed);
+        if depth == 0 {
+            // SeqCst together with the SeqCst load of the active value and get_epoch,
+            // so a writer that swapped the value either sees this epoch or we see the new value
+            let old = self.epoch.fetch_add(1, Ordering::SeqCst);
+            assert!(old % 2 == 0, "{CONCURRENT_USE}");
+        } else {
+            // a concurrent use may have left the depth out of sync with the epoch
+            assert!(
+                self.epoch.load(Ordering::Relaxed) % 2 != 0,
+                "{CONCURRENT_USE}"
+            );
+        }
     }
 
-    /// Increment the epoch counter to leave the read-critical-section
+    /// Increment the epoch counter to leave the read-critical-section,
+    /// when nested only decrement the depth
     ///
     /// # Panics
-    /// - when the Epoch counter even i.e. is inactive/outside the read critical section
+    /// - when the epoch counter is found to be in an inconsistent state, e.g. due to being used concurrently
+    #[cfg(target_has_atomic = "ptr")]
     #[inline]
+    #[track_caller]
     pub(crate) fn leave_rcs(&self) {
-        let old = self.0.fetch_add(1, Ordering::Release);
-        assert!(old % 2 != 0, "Old Epoch counter value should be odd!");
+        let depth = self.depth.load(Ordering::Relaxed);
+        assert!(depth != 0, "{CONCURRENT_USE}");
+        self.depth.store(depth - 1, Ordering::Relaxed);
+        if depth == 1 {
+            let old = self.epoch.fetch_add(1, Ordering::Release);
+            assert!(old % 2 != 0, "{CONCURRENT_USE}");
+        } else {
+            assert!(
+                self.epoch.load(Ordering::Relaxed) % 2 != 0,
+                "{CONCURRENT_USE}"
+            );
+        }
+    }
+
+    /// Enter the read-critical-section until the returned guard is dropped
+    ///
+    /// # Panics
+    /// - when the epoch counter is found to be in an inconsistent state, e.g. due to being used concurrently
+    #[cfg(target_has_atomic = "ptr")]
+    #[inline]
+    #[track_caller]
+    pub(crate) fn enter_rcs_guarded(&self) -> RcsGuard<'_> {
+        self.enter_rcs();
+        RcsGuard(self)
     }
 
     /// Get the current epoch counter value
-    pub(crate) fn get_epoch(&self) -> u8 {
-        self.0.load(Ordering::Acquire)
+    pub(crate) fn get_epoch(&self) -> usize {
+        self.epoch.load(Ordering::SeqCst)
+    }
+}
+
+/// Leaves the read-critical-section when dropped, even when unwinding
+#[cfg(target_has_atomic = "ptr")]
+pub(crate) struct RcsGuard<'a>(&'a EpochCounter);
+
+#[cfg(target_has_atomic = "ptr")]
+impl Drop for RcsGuard<'_> {
+    #[inline]
+    fn drop(&mut self) {
+        self.0.leave_rcs();
     }
 }
 
@@ -109,7 +369,9 @@ impl Default for EpochCounter {
 }
 
 /// ## Safety
-/// `wait_for_epochs` must not return normally until all epoch counters have been witnessed to be even or to have changed
+/// `wait_for_epochs` must not return normally until all epoch counters have been witnessed to be even or to have changed,
+/// `try_wait_for_epochs` must only return `Ok` under the same condition
+/// and `epoch_snapshot` must return `None` or a snapshot of all epoch counters
 ///
 /// The first one is necessary to not get stuck on inactive EpochCounters
 /// The second one is necessary to not get stuck when we race to only witness the EpochCounter in different visits to the read-critical-section.
@@ -123,20 +385,64 @@ pub unsafe trait EpochCounterPool {
     /// - be inactive
     /// - have changed
     fn wait_for_epochs(&self);
+
+    /// Like [`EpochCounterPool::wait_for_epochs`], but may give up instead, e.g. on a timeout or when cancelled
+    ///
+    /// The default implementation uses [`EpochCounterPool::wait_for_epochs`] and never fails.
+    fn try_wait_for_epochs(&self) -> Result<(), WaitError> {
+        self.wait_for_epochs();
+        Ok(())
+    }
+
+    /// Take a snapshot of the epoch counters, to check without blocking whether each has been inactive at least once since
+    ///
+    /// Returns `None` when the pool doesn't support this, callers then need to fall back to [`EpochCounterPool::wait_for_epochs`].
+    fn epoch_snapshot(&self) -> Option<EpochSnapshot> {
+        None
+    }
+
+    /// Check whether the epoch counter belongs to this pool
+    ///
+    /// This is used by debug assertions to catch epoch counters passed to an Rcu with a different pool.
+    /// Returns `None` when the pool can't tell without blocking.
+    /// As this is checked on every raw read in debug builds, the pools of this crate that register epoch counters
+    /// tag them with the id of the pool, instead of going through their epoch counters.
+    fn contains_epoch_counter(&self, _epoch_counter: &EpochCounter) -> Option<bool> {
+        None
+    }
 }
 
-// Safety:
-// `wait_for_epochs` does not return normally until all epoch counters have been witnessed to be even or to have changed
-unsafe impl<F: Fn() -> Vec<Weak<EpochCounter>>> EpochCounterPool for F {
-    fn wait_for_epochs(&self) {
-        // Get the current state of the epoch counters,
-        // we can only drop the old value once we have observed all to be even or to have changed
-        let epochs = self();
+/// The error returned by [`EpochCounterPool::try_wait_for_epochs`] when it gave up waiting
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WaitError;
+
+impl core::fmt::Display for WaitError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("gave up waiting for the epoch counters")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for WaitError {}
+
+/// The state of the active epoch counters of an [`EpochCounterPool`] at some point
+///
+/// See [`EpochCounterPool::epoch_snapshot`]
+pub struct EpochSnapshot {
+    epochs: Vec<(usize, Weak<EpochCounter>)>,
+}
 
-        let mut epochs = epochs
+impl EpochSnapshot {
+    /// Take a snapshot of the given epoch counters
+    pub fn new(epoch_counters: impl IntoIterator<Item = Weak<EpochCounter>>) -> Self {
+        let epochs = epoch_counters
             .into_iter()
             .flat_map(|elem| {
-                let arc = elem.upgrade()?;
+                let Some(arc) = elem.upgrade() else {
+                    // synchronize with the dead thread's accesses in its last critical section, see is_reclaimable
+                    core::sync::atomic::fence(Ordering::Acquire);
+                    return None;
+                };
                 let init_val = arc.get_epoch();
                 if init_val % 2 == 0 {
                     // already even can be ignored
@@ -148,26 +454,557 @@ unsafe impl<F: Fn() -> Vec<Weak<EpochCounter>>> EpochCounterPool for F {
             })
             .collect::<Vec<_>>();
 
-        while !epochs.is_empty() {
-            epochs.retain(|elem| {
-                let Some(arc) = elem.1.upgrade() else {
-                    // as the thread is dead it can't have a pointer to the old arc
-                    return false;
-                };
-                // the epoch counter has not changed so the thread is still in the same instance of the critical section
-                // any different value is ok as
-                // - even values indicate the thread is outside of the critical section
-                // - a different odd value indicates the thread has left the critical section and can subsequently only read the new active_value
-                arc.get_epoch() == elem.0
-            })
+        Self { epochs }
+    }
+
+    /// Check whether all epoch counters have been inactive at least once since the snapshot was taken
+    ///
+    /// Once this returned `true` it will keep returning `true`.
+    pub fn is_reclaimable(&mut self) -> bool {
+        self.epochs.retain(|elem| {
+            let Some(arc) = elem.1.upgrade() else {
+                // as the thread is dead it can't have a pointer to the old arc,
+                // synchronize with the release of its epoch counter to order its accesses before ours,
+                // e.g. loading a boxed thin pointer that is about to be freed
+                core::sync::atomic::fence(Ordering::Acquire);
+                return false;
+            };
+            // the epoch counter has not changed so the thread is still in the same instance of the critical section
+            // any different value is ok as
+            // - even values indicate the thread is outside of the critical section
+            // - a different odd value indicates the thread has left the critical section and can subsequently only read the new active_value
+            arc.get_epoch() == elem.0
+        });
+        self.epochs.is_empty()
+    }
+
+    /// Block until all epoch counters have been inactive at least once since the snapshot was taken,
+    /// backing off between checks
+    pub fn wait(&mut self, backoff: &mut impl Backoff) {
+        while !self.is_reclaimable() {
+            backoff.backoff();
+        }
+    }
+}
+
+/// Waits for each epoch counter of a pool to be inactive at least once since the wait was created
+///
+/// With the `async` feature this is a future, yielding to the executor between checks instead of blocking it,
+/// e.g. to wait for the epochs of a pool in an async runtime, see [`crate::atomic::Arcu::replace_async`].
+/// Checking without blocking requires the pool to support [`EpochCounterPool::epoch_snapshot`],
+/// otherwise the first check blocks in [`EpochCounterPool::wait_for_epochs`].
+pub struct WaitForEpochs<'a, P> {
+    pool: &'a P,
+    snapshot: Option<EpochSnapshot>,
+}
+
+impl<'a, P: EpochCounterPool> WaitForEpochs<'a, P> {
+    /// Start waiting for the epoch counters of the pool
+    pub fn new(pool: &'a P) -> Self {
+        Self {
+            pool,
+            snapshot: pool.epoch_snapshot(),
+        }
+    }
+
+    /// Check whether each epoch counter has been inactive at least once since the wait was created
+    pub fn is_reclaimable(&mut self) -> bool {
+        match &mut self.snapshot {
+            Some(snapshot) => snapshot.is_reclaimable(),
+            None => {
+                self.pool.wait_for_epochs();
+                true
+            }
+        }
+    }
+
+    /// Block until each epoch counter has been inactive at least once since the wait was created
+    pub fn wait(mut self) {
+        let mut backoff = AdaptiveBackoff::default();
+        while !self.is_reclaimable() {
+            backoff.backoff();
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl<P: EpochCounterPool> core::future::Future for WaitForEpochs<'_, P> {
+    type Output = ();
+
+    fn poll(
+        mut self: core::pin::Pin<&mut Self>,
+        cx: &mut core::task::Context<'_>,
+    ) -> core::task::Poll<Self::Output> {
+        if self.is_reclaimable() {
+            core::task::Poll::Ready(())
+        } else {
+            // there is nothing to register the waker with, so re-poll
+            cx.waker().wake_by_ref();
+            core::task::Poll::Pending
         }
     }
 }
 
+/// A strategy for waiting between checks of the epoch counters, see [`EpochSnapshot::wait`] and [`BackoffPool`]
+pub trait Backoff {
+    /// Wait before checking again, called once per failed check
+    fn backoff(&mut self);
+}
+
+/// Spins for the first checks, then yields the thread, then sleeps between checks
+///
+/// Readers usually leave the read critical section quickly, which spinning catches with the least latency.
+/// Waiting on a slow reader yields and then sleeps instead, so the writer doesn't occupy a core the reader may need.
+/// Without the `std` feature this keeps spinning.
+#[derive(Debug, Default, Clone)]
+pub struct AdaptiveBackoff {
+    step: u32,
+}
+
+impl AdaptiveBackoff {
+    const SPIN_STEPS: u32 = 6;
+    #[cfg(feature = "std")]
+    const YIELD_STEPS: u32 = 16;
+}
+
+impl Backoff for AdaptiveBackoff {
+    #[inline]
+    fn backoff(&mut self) {
+        if cfg!(miri) || self.step >= Self::SPIN_STEPS {
+            #[cfg(feature = "std")]
+            if self.step < Self::YIELD_STEPS {
+                std::thread::yield_now();
+            } else {
+                std::thread::sleep(core::time::Duration::from_micros(50));
+            }
+            #[cfg(not(feature = "std"))]
+            core::hint::spin_loop();
+        } else {
+            for _ in 0..1 << self.step {
+                core::hint::spin_loop();
+            }
+        }
+        self.step = self.step.saturating_add(1);
+    }
+}
+
+// Safety:
+// `wait_for_epochs` does not return normally until all epoch counters have been witnessed to be even or to have changed
+unsafe impl<F: Fn() -> Vec<Weak<EpochCounter>>> EpochCounterPool for F {
+    #[cfg_attr(feature = "profiling", inline(never))]
+    fn wait_for_epochs(&self) {
+        // Get the current state of the epoch counters,
+        // we can only drop the old value once we have observed all to be even or to have changed
+        EpochSnapshot::new(self()).wait(&mut AdaptiveBackoff::default());
+    }
+
+    fn epoch_snapshot(&self) -> Option<EpochSnapshot> {
+        Some(EpochSnapshot::new(self()))
+    }
+
+    fn contains_epoch_counter(&self, epoch_counter: &EpochCounter) -> Option<bool> {
+        Some(
+            self()
+                .iter()
+                .any(|elem| core::ptr::eq(elem.as_ptr(), epoch_counter)),
+        )
+    }
+}
+
+/// Called on each iteration of a busy wait
+///
+/// Miri rarely switches between threads, so spinning on a value another thread has yet to change
+/// would take extremely long to interpret. Under Miri we yield to let the other threads make progress,
+/// otherwise this does nothing.
+#[cfg(all(target_has_atomic = "ptr", target_has_atomic = "64"))]
+#[inline]
+pub(crate) fn busy_wait_hint() {
+    #[cfg(miri)]
+    std::thread::yield_now();
+}
+
+/// The array keeps its epoch counters alive, so a reader dying within the read critical section blocks writers forever.
+/// Pools handing out [`Weak`] epoch counters, like the [`GlobalEpochCounterPool`], skip the epoch counters of dead readers.
 // Safety:
 // `wait_for_epochs` does not return normally until all epoch counters have been witnessed to be even or to have changed
 unsafe impl<const N: usize> EpochCounterPool for [Arc<EpochCounter>; N] {
     fn wait_for_epochs(&self) {
         (|| self.iter().map(Arc::downgrade).collect::<Vec<_>>()).wait_for_epochs()
     }
+
+    fn epoch_snapshot(&self) -> Option<EpochSnapshot> {
+        Some(EpochSnapshot::new(self.iter().map(Arc::downgrade)))
+    }
+
+    fn contains_epoch_counter(&self, epoch_counter: &EpochCounter) -> Option<bool> {
+        Some(
+            self.iter()
+                .any(|elem| core::ptr::eq(Arc::as_ptr(elem), epoch_counter)),
+        )
+    }
+}
+
+/// An [`EpochCounterPool`] decorator waiting with the [`Backoff`] strategy `B`, e.g. one suited to a `no_std` target
+///
+/// Pools without [`EpochCounterPool::epoch_snapshot`] wait with their own strategy.
+pub struct BackoffPool<P, B> {
+    inner: P,
+    backoff: PhantomData<fn() -> B>,
+}
+
+impl<P, B> BackoffPool<P, B> {
+    /// Wrap the `inner` pool, waiting with a new `B` for each wait
+    pub fn new(inner: P) -> Self {
+        Self {
+            inner,
+            backoff: PhantomData,
+        }
+    }
+
+    /// Get a reference to the wrapped pool
+    pub fn inner(&self) -> &P {
+        &self.inner
+    }
+}
+
+// Safety:
+// - waiting on a snapshot of all epoch counters of the inner pool waits like the inner pool
+unsafe impl<P: EpochCounterPool, B: Backoff + Default> EpochCounterPool for BackoffPool<P, B> {
+    fn wait_for_epochs(&self) {
+        match self.inner.epoch_snapshot() {
+            Some(mut snapshot) => snapshot.wait(&mut B::default()),
+            None => self.inner.wait_for_epochs(),
+        }
+    }
+
+    fn try_wait_for_epochs(&self) -> Result<(), WaitError> {
+        match self.inner.epoch_snapshot() {
+            Some(mut snapshot) => {
+                snapshot.wait(&mut B::default());
+                Ok(())
+            }
+            None => self.inner.try_wait_for_epochs(),
+        }
+    }
+
+    fn epoch_snapshot(&self) -> Option<EpochSnapshot> {
+        self.inner.epoch_snapshot()
+    }
+
+    fn contains_epoch_counter(&self, epoch_counter: &EpochCounter) -> Option<bool> {
+        self.inner.contains_epoch_counter(epoch_counter)
+    }
+}
+
+/// An [`EpochCounterPool`] decorator limiting the number of writers concurrently waiting for epochs
+///
+/// Writers exceeding the limit block until another writer is done waiting,
+/// rather than all of them spinning in [`EpochCounterPool::wait_for_epochs`] at the same time.
+/// This does not affect reads.
+#[cfg(feature = "std")]
+pub struct ThrottledPool<P> {
+    inner: P,
+    max_writers: usize,
+    active_writers: std::sync::Mutex<usize>,
+    writer_done: std::sync::Condvar,
+}
+
+#[cfg(feature = "std")]
+impl<P> ThrottledPool<P> {
+    /// Wrap the `inner` pool, allowing at most `max_writers` writers to wait for epochs at the same time
+    ///
+    /// # Panics
+    /// - when `max_writers` is zero
+    pub fn new(inner: P, max_writers: usize) -> Self {
+        assert!(max_writers > 0, "At least one writer must be allowed!");
+        Self {
+            inner,
+            max_writers,
+            active_writers: std::sync::Mutex::new(0),
+            writer_done: std::sync::Condvar::new(),
+        }
+    }
+
+    /// Get a reference to the wrapped pool
+    pub fn inner(&self) -> &P {
+        &self.inner
+    }
+}
+
+#[cfg(feature = "std")]
+impl<P> ThrottledPool<P> {
+    /// Block until less than `max_writers` writers are waiting, then count this one
+    fn acquire_permit(&self) -> WriterPermit<'_, P> {
+        let mut active_writers = self
+            .writer_done
+            .wait_while(self.active_writers.lock().unwrap(), |active_writers| {
+                *active_writers >= self.max_writers
+            })
+            .unwrap();
+        *active_writers += 1;
+        drop(active_writers);
+
+        WriterPermit(self)
+    }
+}
+
+#[cfg(feature = "std")]
+struct WriterPermit<'a, P>(&'a ThrottledPool<P>);
+
+#[cfg(feature = "std")]
+impl<P> Drop for WriterPermit<'_, P> {
+    fn drop(&mut self) {
+        *self.0.active_writers.lock().unwrap() -= 1;
+        self.0.writer_done.notify_one();
+    }
+}
+
+// Safety:
+// `wait_for_epochs` only returns after the inner pool's `wait_for_epochs` returned normally
+// and `try_wait_for_epochs` only returns `Ok` when the inner pool's `try_wait_for_epochs` did
+#[cfg(feature = "std")]
+unsafe impl<P: EpochCounterPool> EpochCounterPool for ThrottledPool<P> {
+    fn wait_for_epochs(&self) {
+        // release the permit even when the inner pool panics
+        let _permit = self.acquire_permit();
+        self.inner.wait_for_epochs();
+    }
+
+    fn try_wait_for_epochs(&self) -> Result<(), WaitError> {
+        // release the permit even when the inner pool panics
+        let _permit = self.acquire_permit();
+        self.inner.try_wait_for_epochs()
+    }
+
+    fn contains_epoch_counter(&self, epoch_counter: &EpochCounter) -> Option<bool> {
+        self.inner.contains_epoch_counter(epoch_counter)
+    }
+}
+
+/// An [`EpochCounterPool`] spreading the registration of epoch counters over `SHARDS` independently locked lists
+///
+/// Registering an epoch counter only locks the shard of the registering thread, instead of a single list shared by all threads.
+/// Writers wait for the epoch counters of all shards.
+///
+/// Clones share their epoch counters, so that one clone can be passed to an Arcu and another used for registering.
+#[cfg(feature = "std")]
+#[derive(Clone)]
+pub struct ShardedPool<const SHARDS: usize> {
+    shards: Arc<[std::sync::RwLock<Vec<Weak<EpochCounter>>>; SHARDS]>,
+    id: usize,
+}
+
+#[cfg(feature = "std")]
+impl<const SHARDS: usize> ShardedPool<SHARDS> {
+    /// Create a new ShardedPool without any epoch counters
+    ///
+    /// # Panics
+    /// - when `SHARDS` is zero
+    pub fn new() -> Self {
+        assert!(SHARDS > 0, "At least one shard is required!");
+        Self {
+            shards: Arc::new(core::array::from_fn(|_| std::sync::RwLock::new(Vec::new()))),
+            id: next_pool_id(),
+        }
+    }
+
+    /// Register the epoch counter with the shard of the current thread
+    pub fn register_epoch_counter(&self, epoch_counter: Weak<EpochCounter>) {
+        use core::hash::{Hash, Hasher};
+
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        std::thread::current().id().hash(&mut hasher);
+        let shard = (hasher.finish() % SHARDS as u64) as usize;
+
+        EpochCounter::tag_weak(&epoch_counter, self.id);
+        self.shards[shard].write().unwrap().push(epoch_counter);
+    }
+
+    fn epoch_counters(&self) -> Vec<Weak<EpochCounter>> {
+        self.shards
+            .iter()
+            .flat_map(|shard| shard.read().unwrap().clone())
+            .collect()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<const SHARDS: usize> Default for ShardedPool<SHARDS> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Safety:
+// - `wait_for_epochs` and `epoch_snapshot` cover the epoch counters of all shards
+#[cfg(feature = "std")]
+unsafe impl<const SHARDS: usize> EpochCounterPool for ShardedPool<SHARDS> {
+    fn wait_for_epochs(&self) {
+        (|| self.epoch_counters()).wait_for_epochs()
+    }
+
+    fn epoch_snapshot(&self) -> Option<EpochSnapshot> {
+        Some(EpochSnapshot::new(self.epoch_counters()))
+    }
+
+    fn contains_epoch_counter(&self, epoch_counter: &EpochCounter) -> Option<bool> {
+        epoch_counter.in_pool(self.id)
+    }
+}
+
+/// An [`EpochCounterPool`] with its own epoch counters, instead of the process wide ones of [`GlobalEpochCounterPool`]
+///
+/// Writers of an Rcu using the [`GlobalEpochCounterPool`] wait for the epoch counters of all threads that read any such Rcu.
+/// Giving an Rcu, or a group of Rcus, its own pool limits this to the threads reading them.
+///
+/// Clones share their epoch counters, so that one clone can be passed to each Rcu using the pool.
+/// With the `thread_local_counter` feature, [`crate::atomic::Arcu::read`] registers the current thread with the pool on its first read.
+#[cfg(feature = "std")]
+#[derive(Clone)]
+pub struct LocalEpochCounterPool {
+    epoch_counters: Arc<std::sync::RwLock<Vec<Weak<EpochCounter>>>>,
+    id: usize,
+}
+
+#[cfg(feature = "std")]
+impl LocalEpochCounterPool {
+    /// Create a new LocalEpochCounterPool without any epoch counters
+    pub fn new() -> Self {
+        Self {
+            epoch_counters: Arc::default(),
+            id: next_pool_id(),
+        }
+    }
+
+    /// Register the epoch counter with this pool
+    pub fn register(&self, epoch_counter: Weak<EpochCounter>) {
+        EpochCounter::tag_weak(&epoch_counter, self.id);
+        self.epoch_counters.write().unwrap().push(epoch_counter)
+    }
+
+    /// Remove the epoch counters that have been dropped, returning how many were removed, see [`prune_global_epoch_counters`]
+    pub fn prune(&self) -> usize {
+        let mut epoch_counters = self.epoch_counters.write().unwrap();
+        let before = epoch_counters.len();
+        epoch_counters.retain(|elem| elem.strong_count() > 0);
+        before - epoch_counters.len()
+    }
+
+    /// Calls the provided function with the epoch counter of the current thread for this pool
+    ///
+    /// Per Thread and Pool: On first use creates an epoch counter and registers it with the pool.
+    /// Each pool gets its own epoch counter, so that writing to one pool while reading from another
+    /// doesn't wait for the epoch of the ongoing read.
+    ///
+    /// The epoch counter is looked up by a linear scan over the pools the thread has read from,
+    /// which is short as a thread usually reads from few pools.
+    #[cfg(all(feature = "thread_local_counter", target_has_atomic = "ptr"))]
+    pub(crate) fn with_thread_local_epoch_counter<T>(
+        &self,
+        fun: impl FnOnce(&EpochCounter) -> T,
+    ) -> T {
+        let epoch_counter: *const EpochCounter =
+            LOCAL_THREAD_EPOCH_COUNTERS.with(|registered_pools| {
+                let mut pools = registered_pools.borrow_mut();
+                // the weak references keep the allocations alive, so the address of a dropped pool can't be reused
+                if let Some((_, epoch_counter)) = pools.iter().find(|(pool, _)| {
+                    core::ptr::eq(pool.as_ptr(), Arc::as_ptr(&self.epoch_counters))
+                }) {
+                    return Arc::as_ptr(epoch_counter);
+                }
+
+                pools.retain(|(pool, _)| pool.strong_count() > 0);
+                let epoch_counter = Arc::new(EpochCounter::new());
+                self.register(Arc::downgrade(&epoch_counter));
+                let ptr = Arc::as_ptr(&epoch_counter);
+                pools.push((Arc::downgrade(&self.epoch_counters), epoch_counter));
+                ptr
+            });
+        // the pools are no longer borrowed, as the function may read from a different local pool
+
+        // Safety:
+        // - the thread local keeps an Arc of the epoch counter in the entry of this pool
+        // - entries are only removed once their pool has been dropped, this pool is alive while self is borrowed
+        // - the thread local isn't destroyed while this thread is still running
+        fun(unsafe { &*epoch_counter })
+    }
+
+    fn epoch_counters(&self) -> Vec<Weak<EpochCounter>> {
+        self.epoch_counters.read().unwrap().clone()
+    }
+}
+
+#[cfg(feature = "thread_local_counter")]
+thread_local! {
+    // the LocalEpochCounterPools the current thread has registered with, each with the epoch counter of the thread for that pool
+    #[allow(clippy::type_complexity)]
+    static LOCAL_THREAD_EPOCH_COUNTERS: core::cell::RefCell<
+        Vec<(Weak<std::sync::RwLock<Vec<Weak<EpochCounter>>>>, Arc<EpochCounter>)>,
+    > = const { core::cell::RefCell::new(Vec::new()) };
+}
+
+// Safety:
+// - `wait_for_epochs` and `epoch_snapshot` cover all epoch counters registered with the pool or its clones
+#[cfg(feature = "std")]
+unsafe impl EpochCounterPool for LocalEpochCounterPool {
+    fn wait_for_epochs(&self) {
+        (|| self.epoch_counters()).wait_for_epochs()
+    }
+
+    fn epoch_snapshot(&self) -> Option<EpochSnapshot> {
+        Some(EpochSnapshot::new(self.epoch_counters()))
+    }
+
+    fn contains_epoch_counter(&self, epoch_counter: &EpochCounter) -> Option<bool> {
+        epoch_counter.in_pool(self.id)
+    }
+}
+
+#[cfg(feature = "std")]
+impl Default for LocalEpochCounterPool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// An [`EpochCounterPool`] using the epochs of [`crossbeam_epoch`] instead of [`EpochCounter`]s
+///
+/// This allows codebases already using `crossbeam_epoch` to use a single epoch based reclamation scheme.
+/// No [`EpochCounter`] belongs to this pool, instead an Arcu using it is read
+/// while pinned by a `crossbeam_epoch` guard, see [`crate::atomic::Arcu::read_pinned`].
+///
+/// Writes defer a notification using `crossbeam_epoch` and wait until it has been run,
+/// i.e. until all threads that were pinned when the write happened have been unpinned.
+/// Between checks they back off like the other pools, see [`AdaptiveBackoff`].
+/// Writing while the current thread is pinned will never finish.
+#[cfg(feature = "crossbeam-epoch")]
+pub struct CrossbeamEpochPool;
+
+// Safety:
+// - readers are pinned while loading the pointer and incrementing the strong count
+// - `wait_for_epochs` only returns after crossbeam_epoch ran the deferred function,
+//   which only happens once all threads pinned at the time of deferring have been unpinned
+#[cfg(feature = "crossbeam-epoch")]
+unsafe impl EpochCounterPool for CrossbeamEpochPool {
+    fn wait_for_epochs(&self) {
+        let reclaimable = Arc::new(core::sync::atomic::AtomicBool::new(false));
+
+        let guard = crossbeam_epoch::pin();
+        let notify = Arc::clone(&reclaimable);
+        guard.defer(move || notify.store(true, Ordering::Release));
+        guard.flush();
+        drop(guard);
+
+        let mut backoff = AdaptiveBackoff::default();
+        while !reclaimable.load(Ordering::Acquire) {
+            // pinning and flushing tries to advance the epoch and runs deferred functions that became ready
+            crossbeam_epoch::pin().flush();
+            backoff.backoff();
+        }
+    }
+
+    fn contains_epoch_counter(&self, _epoch_counter: &EpochCounter) -> Option<bool> {
+        // reads usually use crossbeam_epoch guards instead, but epoch counters aren't tracked, so this can't tell
+        None
+    }
 }
@@ -0,0 +1,64 @@
+//! This module contains guards handed out by [`Rcu`] operations
+
+use core::ops::{Deref, DerefMut};
+
+use crate::Rcu;
+
+/// A guard giving mutable access to a copy of an Rcu's value, see [`Rcu::make_mut`]
+///
+/// On drop the modified copy replaces the value of the Rcu.
+/// When dropped during a panic the copy is discarded instead, to not publish a partially modified value.
+pub struct ArcuMutGuard<'a, R: Rcu + ?Sized>
+where
+    R::Item: Sized,
+{
+    rcu: &'a R,
+    // always Some until dropped
+    value: Option<R::Item>,
+}
+
+impl<'a, R: Rcu + ?Sized> ArcuMutGuard<'a, R>
+where
+    R::Item: Sized,
+{
+    pub(crate) fn new(rcu: &'a R, value: R::Item) -> Self {
+        Self {
+            rcu,
+            value: Some(value),
+        }
+    }
+}
+
+impl<R: Rcu + ?Sized> Deref for ArcuMutGuard<'_, R>
+where
+    R::Item: Sized,
+{
+    type Target = R::Item;
+
+    fn deref(&self) -> &Self::Target {
+        self.value.as_ref().expect("value is only taken on drop")
+    }
+}
+
+impl<R: Rcu + ?Sized> DerefMut for ArcuMutGuard<'_, R>
+where
+    R::Item: Sized,
+{
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.value.as_mut().expect("value is only taken on drop")
+    }
+}
+
+impl<R: Rcu + ?Sized> Drop for ArcuMutGuard<'_, R>
+where
+    R::Item: Sized,
+{
+    fn drop(&mut self) {
+        if std::thread::panicking() {
+            return;
+        }
+        if let Some(value) = self.value.take() {
+            self.rcu.replace(value);
+        }
+    }
+}
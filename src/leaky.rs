@@ -0,0 +1,123 @@
+//! This module contains [`LeakyArcu`], an Rcu without per read reference counting that keeps replaced values until collected
+
+use alloc::boxed::Box;
+use core::{
+    marker::PhantomData,
+    sync::atomic::{AtomicPtr, Ordering},
+};
+use std::sync::Mutex;
+
+/// An Rcu whose reads return plain references, keeping replaced values alive until [`LeakyArcu::gc`]
+///
+/// Reads neither touch a reference count nor an epoch counter, they only load the active pointer.
+/// In turn replaced values can't be reclaimed while the `LeakyArcu` is shared,
+/// as any reference returned by [`LeakyArcu::read`] may still point to them.
+/// They are retired instead and only dropped by [`LeakyArcu::gc`], which requires exclusive access,
+/// or when the `LeakyArcu` is dropped.
+///
+/// This trades memory for cheap reads, so it is meant for read-dominated values that are rarely replaced,
+/// e.g. configuration, with [`LeakyArcu::gc`] being called at a point without readers, like a reload barrier.
+pub struct LeakyArcu<T> {
+    // Safety invariant
+    // - the pointer has been created with Box::into_raw
+    // - it is only freed once the LeakyArcu is exclusively borrowed
+    active_value: AtomicPtr<T>,
+    // Safety invariant
+    // - the pointers have been created with Box::into_raw
+    // - they are only freed once the LeakyArcu is exclusively borrowed
+    retired: Mutex<Vec<*mut T>>,
+    phantom: PhantomData<Box<T>>,
+}
+
+// Safety:
+// - values are created, dropped and potentially replaced on different threads, so T must be Send
+// - the retired pointers are only dereferenced by gc and drop, which have exclusive access
+unsafe impl<T: Send> Send for LeakyArcu<T> {}
+
+// Safety:
+// - values are shared between all threads reading, so T must be Sync
+// - values are created and retired on different threads, so T must be Send
+unsafe impl<T: Send + Sync> Sync for LeakyArcu<T> {}
+
+impl<T: core::fmt::Debug> core::fmt::Debug for LeakyArcu<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("LeakyArcu")
+            .field("active_value", self.read())
+            .field("retired", &self.retired())
+            .finish()
+    }
+}
+
+impl<T> LeakyArcu<T> {
+    /// Create a new LeakyArcu with the given initial value
+    pub fn new(initial: T) -> Self {
+        Self {
+            active_value: AtomicPtr::new(Box::into_raw(Box::new(initial))),
+            retired: Mutex::new(Vec::new()),
+            phantom: PhantomData,
+        }
+    }
+
+    /// Read the current value
+    ///
+    /// The reference stays valid while the LeakyArcu is borrowed, even when the value is replaced in the meantime.
+    #[inline]
+    pub fn read(&self) -> &T {
+        let ptr = self.active_value.load(Ordering::Acquire);
+
+        // Safety:
+        // - the ptr was created in LeakyArcu::new or LeakyArcu::replace with Box::into_raw
+        // - the value is only dropped once the LeakyArcu is exclusively borrowed,
+        //   which can't happen while the returned reference borrows it
+        unsafe { &*ptr }
+    }
+
+    /// Replace the current value, retiring the old one until the next [`LeakyArcu::gc`]
+    pub fn replace(&self, new_value: T) {
+        let new_value = Box::into_raw(Box::new(new_value));
+        let old_value = self.active_value.swap(new_value, Ordering::AcqRel);
+        self.retired.lock().unwrap().push(old_value);
+    }
+
+    /// The number of replaced values that are waiting to be collected
+    pub fn retired(&self) -> usize {
+        self.retired.lock().unwrap().len()
+    }
+
+    /// Drop all replaced values
+    ///
+    /// Requiring exclusive access guarantees that there are no references to them left.
+    pub fn gc(&mut self) {
+        let retired = core::mem::take(
+            self.retired
+                .get_mut()
+                .unwrap_or_else(std::sync::PoisonError::into_inner),
+        );
+        for old_value in retired {
+            // Safety:
+            // - the ptr was created with Box::into_raw
+            // - the value has been replaced, so it's only in the retired list which we just took
+            // - we have exclusive access, so there can't be any references to it
+            drop(unsafe { Box::from_raw(old_value) });
+        }
+    }
+
+    /// Get mutable access to the current value
+    pub fn get_mut(&mut self) -> &mut T {
+        // Safety:
+        // - the ptr was created in LeakyArcu::new or LeakyArcu::replace with Box::into_raw
+        // - we have exclusive access, so there can't be any other references to it
+        unsafe { &mut *self.active_value.load(Ordering::Acquire) }
+    }
+}
+
+impl<T> Drop for LeakyArcu<T> {
+    fn drop(&mut self) {
+        self.gc();
+
+        // Safety:
+        // - the ptr was created in LeakyArcu::new or LeakyArcu::replace with Box::into_raw
+        // - we have exclusive access, so there can't be any references to it
+        drop(unsafe { Box::from_raw(self.active_value.load(Ordering::Acquire)) });
+    }
+}
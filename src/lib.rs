@@ -8,22 +8,72 @@ extern crate alloc;
 
 pub mod epoch_counters;
 
-use alloc::sync::Arc;
+use alloc::sync::{Arc, Weak};
+#[cfg(feature = "thread_local_counter")]
+use core::ops::Deref;
 use epoch_counters::EpochCounterPool;
 
 use crate::epoch_counters::EpochCounter;
 
+pub mod arc_like;
+#[cfg(target_has_atomic = "ptr")]
 pub mod atomic;
 pub mod rwlock;
 
+/// The atomics based Rcu is unavailable on this target, as it lacks pointer sized atomics
+///
+/// `atomic::Arcu` falls back to the RwLock based [`rwlock::Arcu`] instead.
+#[cfg(not(target_has_atomic = "ptr"))]
+pub mod atomic {
+    /// Falls back to [`crate::rwlock::Arcu`] as this target lacks pointer sized atomics
+    #[deprecated(
+        note = "the atomics based Arcu is unavailable on this target as it lacks pointer sized atomics, use rwlock::Arcu instead"
+    )]
+    pub type Arcu<T, P> = crate::rwlock::Arcu<T, P>;
+}
+
 pub mod rcu_ref;
 
+pub mod reclaim;
+
+#[cfg(feature = "thread_local_counter")]
+pub mod guard;
+
+#[cfg(feature = "thread_local_counter")]
+pub mod cache;
+
+#[cfg(feature = "global_counters")]
+pub mod scoped;
+
+#[cfg(feature = "std")]
+pub mod branded;
+
+#[cfg(all(feature = "std", target_has_atomic = "ptr"))]
+pub mod recycling;
+
+#[cfg(feature = "std")]
+pub mod leaky;
+
+#[cfg(all(feature = "std", target_has_atomic = "ptr"))]
+pub mod deferred;
+
+#[cfg(target_has_atomic = "ptr")]
+pub mod pinned;
+
+#[cfg(all(target_has_atomic = "ptr", target_has_atomic = "64"))]
+pub mod seq;
+
+#[cfg(all(feature = "thread_local_counter", target_has_atomic = "ptr"))]
+pub mod sharded;
+
 mod doc_tests;
 
 /// An abstract Rcu to abstract over the atomic based [`atomic::Arcu`] and the RwLock based [`rwlock::Arcu`]
 pub trait Rcu {
     /// The type contained in this Rcu
-    type Item;
+    ///
+    /// This may be unsized, e.g. a slice or `str`, as the value is only ever handled behind an [`Arc`].
+    type Item: ?Sized;
 
     /// The type for the pool of epoch counters used by this Rcu
     type Pool: EpochCounterPool;
@@ -49,14 +99,136 @@ pub trait Rcu {
     where
         Self: Rcu<Pool = epoch_counters::GlobalEpochCounterPool>,
     {
-        let arc = crate::epoch_counters::with_thread_local_epoch_counter(|epoch_counter| {
+        rcu_ref::RcuRef::<Self::Item, Self::Item>::new(self.read_arc())
+    }
+
+    /// Read the value of the Rcu for the current epoch as an [`Arc`]
+    ///
+    /// Like [`Rcu::read`], but without wrapping the snapshot in an [`rcu_ref::RcuRef`], e.g. to store it elsewhere.
+    #[cfg(feature = "thread_local_counter")]
+    fn read_arc(&self) -> Arc<Self::Item>
+    where
+        Self: Rcu<Pool = epoch_counters::GlobalEpochCounterPool>,
+    {
+        crate::epoch_counters::with_thread_local_epoch_counter(|epoch_counter| {
             // Safety:
             // - we just registered the epoch counter
             // - this is a thread local epoch counter that is only used here, so there can't be a concurrent use
             unsafe { self.raw_read(epoch_counter) }
-        });
+        })
+    }
+
+    /// Read the value of the Rcu for the current epoch as a [`rcu_ref::SharedSnapshot`]
+    ///
+    /// Unlike the [`rcu_ref::RcuRef`] returned by [`Rcu::read`] the snapshot implements [`Clone`],
+    /// see [`rcu_ref::SharedSnapshot`] for when to use which.
+    #[cfg(feature = "thread_local_counter")]
+    fn snapshot(&self) -> rcu_ref::SharedSnapshot<Self::Item>
+    where
+        Self: Rcu<Pool = epoch_counters::GlobalEpochCounterPool>,
+    {
+        rcu_ref::SharedSnapshot::from(self.read_arc())
+    }
+
+    /// Read the value of the Rcu for the current epoch without blocking
+    ///
+    /// Like [`Rcu::read`], but returns `None` instead of blocking
+    /// when the initial registration of the epoch counter on this thread would block.
+    /// Once registered, this will always return `Some`.
+    #[cfg(feature = "thread_local_counter")]
+    fn try_read(&self) -> Option<rcu_ref::RcuRef<Self::Item, Self::Item>>
+    where
+        Self: Rcu<Pool = epoch_counters::GlobalEpochCounterPool>,
+    {
+        let arc = crate::epoch_counters::try_with_thread_local_epoch_counter(|epoch_counter| {
+            // Safety:
+            // - we just registered the epoch counter
+            // - this is a thread local epoch counter that is only used here, so there can't be a concurrent use
+            unsafe { self.raw_read(epoch_counter) }
+        })?;
+
+        Some(rcu_ref::RcuRef::<Self::Item, Self::Item>::new(arc))
+    }
+
+    /// Read the value of the Rcu for the current epoch, failing instead of panicking when the backend is unusable
+    ///
+    /// The atomics based [`atomic::Arcu`] never fails, the RwLock based [`rwlock::Arcu`] fails when its lock has been poisoned,
+    /// which lets generic code handle this uniformly. As the poison can't be cleared, see [`rwlock::Arcu`],
+    /// it keeps failing from then on. See [`Rcu::read`]
+    #[cfg(feature = "thread_local_counter")]
+    fn try_read_checked(&self) -> Result<rcu_ref::RcuRef<Self::Item, Self::Item>, BackendError>
+    where
+        Self: Rcu<Pool = epoch_counters::GlobalEpochCounterPool>,
+    {
+        Ok(self.read())
+    }
+
+    /// Call the provided function with a reference to the value of the Rcu for the current epoch
+    ///
+    /// Unlike [`Rcu::read`] this may avoid touching the strong count of the current value,
+    /// by calling the function inside the read critical section.
+    ///
+    /// ## Blocking
+    /// Writers will block until the function returns, so it should be kept short.
+    /// Writing to the same Rcu from within the function will deadlock.
+    /// Reading from within the function is fine, nested reads keep the thread's read critical section active.
+    #[cfg(feature = "thread_local_counter")]
+    fn with_read_borrowed<R>(&self, fun: impl FnOnce(&Self::Item) -> R) -> R
+    where
+        Self: Rcu<Pool = epoch_counters::GlobalEpochCounterPool>,
+    {
+        crate::epoch_counters::with_thread_local_epoch_counter(|epoch_counter| {
+            // Safety:
+            // - we just registered the epoch counter
+            // - this is a thread local epoch counter that is only used here, so there can't be a concurrent use
+            unsafe { self.raw_with_read(epoch_counter, fun) }
+        })
+    }
 
-        rcu_ref::RcuRef::<Self::Item, Self::Item>::new(arc)
+    /// Call the C callback with a pointer to the value of the Rcu for the current epoch, e.g. for FFI
+    ///
+    /// This is [`Rcu::with_read_borrowed`] for C code, `ctx` is passed through to the callback unchanged.
+    /// The pointer is only valid for the duration of the callback and must not be retained past it.
+    /// This requires a sized value, as pointers to slices and `str` are not FFI-safe.
+    ///
+    /// ## Blocking
+    /// Writers will block until the callback returns, see [`Rcu::with_read_borrowed`].
+    #[cfg(feature = "thread_local_counter")]
+    fn with_read_raw(
+        &self,
+        ctx: *mut core::ffi::c_void,
+        callback: extern "C" fn(*mut core::ffi::c_void, *const Self::Item),
+    ) where
+        Self: Rcu<Pool = epoch_counters::GlobalEpochCounterPool>,
+        Self::Item: Sized,
+    {
+        self.with_read_borrowed(|value| callback(ctx, value));
+    }
+
+    /// Iterate over the elements of the current value, e.g. for an Rcu containing a [`Vec`]
+    ///
+    /// The value is read once, the iterator keeps this snapshot alive for its whole lifetime,
+    /// so iterating isn't affected by later writes, see [`rcu_ref::RcuRef::iter`].
+    #[cfg(feature = "thread_local_counter")]
+    fn iter_snapshot<U>(&self) -> rcu_ref::RcuIter<Self::Item, U>
+    where
+        Self: Rcu<Pool = epoch_counters::GlobalEpochCounterPool>,
+        Self::Item: AsRef<[U]>,
+    {
+        rcu_ref::RcuRef::iter(self.read())
+    }
+
+    /// Read the element at `index` of the current value, e.g. for an Rcu containing a [`Vec`] or an `Arc<[T]>`
+    ///
+    /// Returns `None` when `index` is out of bounds of the current value.
+    /// The returned reference keeps the whole value alive, so it stays valid across writes, see [`rcu_ref::RcuRef::get`].
+    #[cfg(feature = "thread_local_counter")]
+    fn get<U>(&self, index: usize) -> Option<rcu_ref::RcuRef<Self::Item, U>>
+    where
+        Self: Rcu<Pool = epoch_counters::GlobalEpochCounterPool>,
+        Self::Item: AsRef<[U]>,
+    {
+        rcu_ref::RcuRef::get(self.read(), index)
     }
 
     /// Replace the Rcu's content with a new value
@@ -67,11 +239,108 @@ pub trait Rcu {
     /// This will block until the old value can be reclaimed,
     /// i.e. all threads witnessed to be in the read critical sections
     /// have been witnessed to have left the critical section at least once
+    ///
+    /// Leaving the read critical section only guarantees that no reader is about to increment the strong count,
+    /// readers may keep their snapshots for longer. As such the strong count of the returned value
+    /// can be larger than one without indicating an issue with the epoch counters.
     fn replace(&self, new_value: impl Into<Arc<Self::Item>>) -> Arc<Self::Item>;
 
+    /// Replace the Rcu's content with a new value, additionally returning a [`Weak`] to the new value
+    ///
+    /// The new value stays alive while it is the Rcu's value and while readers hold snapshots of it,
+    /// so the weak reference can be used to observe when the new value has been replaced and all its readers are done.
+    /// See [`Rcu::replace`]
+    fn replace_tracked(
+        &self,
+        new_value: impl Into<Arc<Self::Item>>,
+    ) -> (Arc<Self::Item>, Weak<Self::Item>) {
+        let new_value = new_value.into();
+        let tracker = Arc::downgrade(&new_value);
+        (self.replace(new_value), tracker)
+    }
+
+    /// Replace the Rcu's content with a new value, handing the old value to `sink` instead of returning it
+    ///
+    /// The sink is called once the old value can be reclaimed, see [`Rcu::replace`].
+    /// This lets the caller decide where an expensive drop happens, e.g. by sending the old value to a work queue.
+    fn replace_with_drop_sink(
+        &self,
+        new_value: impl Into<Arc<Self::Item>>,
+        sink: impl FnOnce(Arc<Self::Item>) + Send,
+    ) {
+        sink(self.replace(new_value))
+    }
+
+    /// Replace the Rcu's content with a new value, returning the old value as an [`rcu_ref::RcuRef`]
+    ///
+    /// The old value stays alive and readable while the returned reference is held,
+    /// e.g. to keep serving it during a handover to the new value, using the projections of [`rcu_ref::RcuRef`].
+    /// See [`Rcu::replace`]
+    fn replace_returning_old_ref(
+        &self,
+        new_value: impl Into<Arc<Self::Item>>,
+    ) -> rcu_ref::RcuRef<Self::Item, Self::Item> {
+        rcu_ref::RcuRef::new(self.replace(new_value))
+    }
+
+    /// Replace the Rcu's content with a new value, unless the pool gives up waiting for the old value to become reclaimable
+    ///
+    /// Like [`Rcu::replace`], but for pools whose [`EpochCounterPool::try_wait_for_epochs`] can fail, e.g. on a timeout.
+    /// The new value is active either way, on failure the error holds on to the old value
+    /// and lets the caller retry or leak it, see [`reclaim::ReclaimError`].
+    ///
+    /// The default implementation uses [`Rcu::replace`] and never fails.
+    fn try_replace(
+        &self,
+        new_value: impl Into<Arc<Self::Item>>,
+    ) -> Result<Arc<Self::Item>, reclaim::ReclaimError<'_, Self::Item>> {
+        Ok(self.replace(new_value))
+    }
+
+    /// Deserialize a new value and replace the Rcu's content with it
+    ///
+    /// Returns the old value, see [`Rcu::replace`].
+    /// The Rcu is left unchanged when deserializing fails.
+    #[cfg(feature = "serde")]
+    fn deserialize_replace<'de, D>(&self, deserializer: D) -> Result<Arc<Self::Item>, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+        Self::Item: serde::Deserialize<'de>,
+    {
+        let new_value = <Self::Item as serde::Deserialize>::deserialize(deserializer)?;
+        Ok(self.replace(new_value))
+    }
+
+    /// Encode the value of the Rcu for the current epoch with [`bincode`], e.g. to hand it to another process
+    ///
+    /// The value is read once, so the encoding is of a single point-in-time snapshot even when written to concurrently.
+    #[cfg(all(feature = "bincode", feature = "thread_local_counter"))]
+    fn encode_snapshot(&self) -> Result<Vec<u8>, bincode::Error>
+    where
+        Self: Rcu<Pool = epoch_counters::GlobalEpochCounterPool>,
+        Self::Item: serde::Serialize,
+    {
+        bincode::serialize(&*self.read())
+    }
+
+    /// Decode a new value encoded with [`bincode`] and replace the Rcu's content with it
+    ///
+    /// Returns the old value, see [`Rcu::replace`].
+    /// The Rcu is left unchanged when decoding fails.
+    #[cfg(feature = "bincode")]
+    fn decode_replace(&self, bytes: &[u8]) -> Result<Arc<Self::Item>, bincode::Error>
+    where
+        Self::Item: serde::de::DeserializeOwned,
+    {
+        let new_value = bincode::deserialize::<Self::Item>(bytes)?;
+        Ok(self.replace(new_value))
+    }
+
     /// Update the Rcu using the provided update function
     /// Retries when the Rcu has been updated/replaced between reading the old value and writing the new value
     /// Aborts when the update function returns None
+    ///
+    /// A panic in the update function is propagated, leaving the Rcu unchanged
     #[cfg(feature = "thread_local_counter")]
     fn try_update<F, R>(&self, mut update: F) -> Option<Arc<Self::Item>>
     where
@@ -87,15 +356,234 @@ pub trait Rcu {
         })
     }
 
+    /// Update the Rcu using the provided update function, which is also passed the zero-based attempt number
+    ///
+    /// Like [`Rcu::try_update`], the attempt number lets the update function adapt to contention,
+    /// e.g. by giving up with `None` after a number of retries.
+    ///
+    /// The attempt number only advances when the previous attempt failed because a different writer replaced the value,
+    /// see [`Rcu::raw_try_update_indexed`]. A compare exchange failing spuriously, see [`atomic::Arcu::with_weak_updates`],
+    /// calls the update function again with the same attempt number, so it doesn't count as contention.
+    #[cfg(feature = "thread_local_counter")]
+    fn try_update_indexed(
+        &self,
+        update: impl FnMut(&Self::Item, usize) -> Option<Arc<Self::Item>>,
+    ) -> Option<Arc<Self::Item>>
+    where
+        Self: Rcu<Pool = epoch_counters::GlobalEpochCounterPool>,
+    {
+        // Safety:
+        // epoch_counter is thread local and as such can't be in use concurrently
+        // get_epoch_counters returns the list of all registered epoch counters
+        crate::epoch_counters::with_thread_local_epoch_counter(|epoch_counter| unsafe {
+            self.raw_try_update_indexed(update, epoch_counter)
+        })
+    }
+
+    /// Update the Rcu using the provided update function, giving up after `max_attempts` failed attempts
+    ///
+    /// Like [`Rcu::try_update`], but instead of retrying forever while other writers keep winning the race,
+    /// this returns [`UpdateExhausted`] once `max_attempts` attempts to write have failed,
+    /// letting the caller back off or fall back to a different strategy.
+    /// Only attempts failing because a different writer replaced the value count, see [`Rcu::try_update_indexed`].
+    /// Returns `Ok(None)` when the update function aborted with `None`.
+    ///
+    /// With `max_attempts` of zero no attempt may fail, so this gives up right away without calling `update`.
+    #[cfg(feature = "thread_local_counter")]
+    fn try_update_bounded<F, R>(
+        &self,
+        max_attempts: usize,
+        mut update: F,
+    ) -> Result<Option<Arc<Self::Item>>, UpdateExhausted>
+    where
+        Self: Rcu<Pool = epoch_counters::GlobalEpochCounterPool>,
+        F: FnMut(&Self::Item) -> Option<R>,
+        R: Into<Arc<Self::Item>>,
+    {
+        let mut exhausted = false;
+        let mut attempts = 0;
+        let old_value = self.try_update_indexed(|old, failed| {
+            if failed >= max_attempts {
+                exhausted = true;
+                return None;
+            }
+            attempts += 1;
+            update(old).map(Into::into)
+        });
+
+        if exhausted {
+            Err(UpdateExhausted {
+                attempts,
+                conflicts: max_attempts,
+            })
+        } else {
+            Ok(old_value)
+        }
+    }
+
+    /// Update the Rcu using the provided update function, unless the new value equals the current value
+    ///
+    /// Like [`Rcu::try_update`] this retries when the Rcu has been updated/replaced in between,
+    /// comparing the new value against the latest read value each time.
+    /// Returns `None` without replacing the value when it is unchanged,
+    /// this also avoids waiting for the epochs of a no-op write.
+    #[cfg(feature = "thread_local_counter")]
+    fn update_if_changed(
+        &self,
+        mut update: impl FnMut(&Self::Item) -> Arc<Self::Item>,
+    ) -> Option<Arc<Self::Item>>
+    where
+        Self: Rcu<Pool = epoch_counters::GlobalEpochCounterPool>,
+        Self::Item: PartialEq,
+    {
+        self.try_update(|old| {
+            let new = update(old);
+            (*new != *old).then_some(new)
+        })
+    }
+
+    /// Replace the Rcu's content with a new value built from the current value, if the condition accepts the current value
+    ///
+    /// Unlike [`Rcu::try_update`] the new value is only built once the condition passed, so expensive values aren't built
+    /// just to be discarded. Like [`Rcu::try_update`] this retries when the Rcu has been updated/replaced in between,
+    /// checking the condition again and building a new value from the new current value.
+    /// A weak compare exchange failing spuriously also builds the value again, see [`atomic::Arcu::with_weak_updates`].
+    /// Returns the previous value when it has been replaced.
+    #[cfg(feature = "thread_local_counter")]
+    fn replace_if_with(
+        &self,
+        mut should: impl FnMut(&Self::Item) -> bool,
+        mut make: impl FnMut(&Self::Item) -> Arc<Self::Item>,
+    ) -> Option<Arc<Self::Item>>
+    where
+        Self: Rcu<Pool = epoch_counters::GlobalEpochCounterPool>,
+    {
+        self.try_update(|old| should(old).then(|| make(old)))
+    }
+
+    /// Replace the Rcu's content with a new value if the validation function accepts it
+    ///
+    /// The validation function is called with the current and the new value,
+    /// on `Ok` the new value replaces the current value and the previous value is returned,
+    /// on `Err` the Rcu is left unchanged and the error is returned together with the rejected value.
+    /// Like [`Rcu::try_update`] this retries, validating again against the new current value,
+    /// when the Rcu has been updated/replaced between validating and writing.
+    #[cfg(feature = "thread_local_counter")]
+    #[allow(clippy::type_complexity)]
+    fn replace_validated<E>(
+        &self,
+        new_value: impl Into<Arc<Self::Item>>,
+        mut validate: impl FnMut(&Self::Item, &Self::Item) -> Result<(), E>,
+    ) -> Result<Arc<Self::Item>, (E, Arc<Self::Item>)>
+    where
+        Self: Rcu<Pool = epoch_counters::GlobalEpochCounterPool>,
+    {
+        let new_value = new_value.into();
+        let mut error = None;
+
+        let old_value = self.try_update(|current| match validate(current, &new_value) {
+            Ok(()) => Some(Arc::clone(&new_value)),
+            Err(err) => {
+                error = Some(err);
+                None
+            }
+        });
+
+        match old_value {
+            Some(old_value) => Ok(old_value),
+            None => Err((
+                error.expect("try_update only aborts when the validation failed"),
+                new_value,
+            )),
+        }
+    }
+
+    /// Read the current value and decide with `fun` whether to replace it, returning the result of the decision
+    ///
+    /// `fun` decides with a [`ReadModify`] to
+    /// - commit a new value, which is installed when the value hasn't been replaced in the meantime, otherwise `fun` is called again,
+    /// - abort, leaving the Rcu unchanged, or
+    /// - retry, e.g. when `fun` observed an inconsistency, calling `fun` again with a fresh read.
+    ///
+    /// The result of the last call to `fun` is returned once it either committed successfully or aborted.
+    /// `fun` may also be called again after committing when the value hasn't been replaced,
+    /// as a weak compare exchange may fail spuriously, see [`atomic::Arcu::with_weak_updates`].
+    /// This generalizes [`Rcu::try_update`] and friends to return arbitrary results.
+    #[cfg(feature = "thread_local_counter")]
+    fn read_modify<N, R>(&self, mut fun: impl FnMut(&Self::Item) -> ReadModify<N, R>) -> R
+    where
+        Self: Rcu<Pool = epoch_counters::GlobalEpochCounterPool>,
+        N: Into<Arc<Self::Item>>,
+    {
+        loop {
+            let mut decision = None;
+
+            self.try_update(|current| match fun(current) {
+                ReadModify::Commit(new_value, result) => {
+                    decision = Some(Some(result));
+                    Some(new_value)
+                }
+                ReadModify::Abort(result) => {
+                    decision = Some(Some(result));
+                    None
+                }
+                ReadModify::Retry => {
+                    decision = Some(None);
+                    None
+                }
+            });
+
+            if let Some(result) =
+                decision.expect("try_update calls the update function at least once")
+            {
+                return result;
+            }
+        }
+    }
+
+    /// Get mutable access to a copy of the current value, similar to [`Arc::make_mut`]
+    ///
+    /// The current value is cloned, when the returned guard is dropped the modified copy replaces the value of the Rcu.
+    ///
+    /// Writes between reading and replacing will be overwritten,
+    /// i.e. the committed value may be based on a stale read.
+    /// Use [`Rcu::try_update`] to retry on concurrent writes instead.
+    #[cfg(feature = "thread_local_counter")]
+    fn make_mut(&self) -> guard::ArcuMutGuard<'_, Self>
+    where
+        Self: Rcu<Pool = epoch_counters::GlobalEpochCounterPool>,
+        Self::Item: Clone,
+    {
+        let value = self.read().deref().clone();
+        guard::ArcuMutGuard::new(self, value)
+    }
+
     /// ## Safety
     /// - The epoch counter must not be used concurrently
     /// - The epoch counter must belong to the EpochCounterPool of this Rcu
     unsafe fn raw_read(&self, epoch_counter: &EpochCounter) -> Arc<Self::Item>;
 
+    /// Call the provided function with a reference to the value of the Rcu for the current epoch
+    ///
+    /// The default implementation reads the value with [`Rcu::raw_read`].
+    ///
+    /// ## Safety
+    /// - The epoch counter must not be used concurrently
+    /// - The epoch counter must belong to the EpochCounterPool of this Rcu
+    unsafe fn raw_with_read<R>(
+        &self,
+        epoch_counter: &EpochCounter,
+        fun: impl FnOnce(&Self::Item) -> R,
+    ) -> R {
+        fun(&self.raw_read(epoch_counter))
+    }
+
     /// Update the Rcu using the provided update function
     /// Retries when the Rcu has been updated/replaced between reading the old value and writing the new value
     /// Aborts when the update function returns None
     ///
+    /// A panic in the update function is propagated, leaving the Rcu unchanged
+    ///
     /// ## Safety
     /// - The epoch counter must not be used concurrently
     /// - The epoch counter must belong to the EpochCounterPool of this Rcu
@@ -104,4 +592,82 @@ pub trait Rcu {
         update: impl FnMut(&Self::Item) -> Option<Arc<Self::Item>>,
         epoch_counter: &EpochCounter,
     ) -> Option<Arc<Self::Item>>;
+
+    /// Update the Rcu using the provided update function, which is also passed the number of attempts that failed so far
+    ///
+    /// Like [`Rcu::raw_try_update`], but only attempts failing because a different writer replaced the value are counted,
+    /// attempts retried after a spurious compare exchange failure are not, see [`Rcu::try_update_indexed`].
+    ///
+    /// ## Safety
+    /// - The epoch counter must not be used concurrently
+    /// - The epoch counter must belong to the EpochCounterPool of this Rcu
+    unsafe fn raw_try_update_indexed(
+        &self,
+        update: impl FnMut(&Self::Item, usize) -> Option<Arc<Self::Item>>,
+        epoch_counter: &EpochCounter,
+    ) -> Option<Arc<Self::Item>>;
+}
+
+/// The decision of the function passed to [`Rcu::read_modify`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReadModify<N, R> {
+    /// Replace the value read with the new value and return the result
+    Commit(N, R),
+    /// Leave the Rcu unchanged and return the result
+    Abort(R),
+    /// Read the value again and decide anew
+    Retry,
+}
+
+/// The error of [`Rcu::try_read_checked`] when the backend of the Rcu is unusable
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum BackendError {
+    /// The lock of the RwLock based [`rwlock::Arcu`] has been poisoned
+    Poisoned,
+}
+
+impl core::fmt::Display for BackendError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            BackendError::Poisoned => f.write_str("the lock of the Rcu has been poisoned"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for BackendError {}
+
+/// The error of [`Rcu::try_update_bounded`] when all attempts to write failed due to concurrent writes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UpdateExhausted {
+    attempts: usize,
+    conflicts: usize,
+}
+
+impl UpdateExhausted {
+    /// The number of attempts made, i.e. calls of the update function
+    ///
+    /// This includes attempts retried after a spurious compare exchange failure, so it may exceed [`UpdateExhausted::conflicts`].
+    pub fn attempts(&self) -> usize {
+        self.attempts
+    }
+
+    /// The number of attempts that failed because a different writer replaced the value, i.e. the `max_attempts` given up at
+    pub fn conflicts(&self) -> usize {
+        self.conflicts
+    }
 }
+
+impl core::fmt::Display for UpdateExhausted {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "gave up updating after {} attempts lost to concurrent writes",
+            self.conflicts
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for UpdateExhausted {}
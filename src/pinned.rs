@@ -0,0 +1,117 @@
+//! This module contains [`PinnedArcu`], an [`Arcu`] whose values are pinned
+
+use alloc::sync::Arc;
+use core::{ops::Deref, pin::Pin};
+
+use crate::{
+    atomic::Arcu,
+    epoch_counters::{EpochCounter, EpochCounterPool},
+    Rcu,
+};
+
+/// An [`Arcu`] whose values are pinned, e.g. for self-referential values
+///
+/// ## Soundness
+/// Pinning requires that a value is neither moved nor invalidated until it is dropped.
+/// The value in an [`Arc`] is only ever moved out via an `Arc<T>`, e.g. by [`Arc::try_unwrap`] or [`Arc::get_mut`].
+/// A plain [`Arcu`] hands out `Arc<T>`s via reads and replaces, so it can't give out pinned references.
+///
+/// `PinnedArcu` never gives out an `Arc<T>`, only [`Pin<Arc<T>>`] which doesn't allow moving the value.
+/// Values are pinned when they are put into the `PinnedArcu` and stay pinned until dropped,
+/// either by the `PinnedArcu` or by the last holder of a `Pin<Arc<T>>`.
+/// As the value is only ever shared, reads can hand out [`Pin<&T>`].
+pub struct PinnedArcu<T, P> {
+    // Safety invariant
+    // - the Arcs are never given out without being pinned
+    arcu: Arcu<T, P>,
+}
+
+impl<T, P: EpochCounterPool> PinnedArcu<T, P> {
+    /// Create a new PinnedArcu with the given initial value
+    pub fn new(initial: T, epoch_counter_pool: P) -> Self {
+        Self {
+            arcu: Arcu::new(initial, epoch_counter_pool),
+        }
+    }
+
+    /// Create a new PinnedArcu from an already pinned initial value
+    pub fn from_pin(initial: Pin<Arc<T>>, epoch_counter_pool: P) -> Self {
+        // Safety:
+        // - the arc is only given out pinned again
+        let initial = unsafe { Pin::into_inner_unchecked(initial) };
+        Self {
+            arcu: Arcu::new(initial, epoch_counter_pool),
+        }
+    }
+
+    /// Read the value of the Rcu for the current epoch, see [`Rcu::raw_read`]
+    ///
+    /// ## Safety
+    /// - The epoch counter must not be used concurrently
+    /// - The epoch counter must belong to the EpochCounterPool of this Rcu
+    pub unsafe fn raw_read_pin(&self, epoch_counter: &EpochCounter) -> ReadPinned<T> {
+        // Safety: the caller upholds the safety requirements
+        let snapshot = unsafe { self.arcu.raw_read(epoch_counter) };
+
+        // Safety:
+        // - the value has been pinned when it was put into the PinnedArcu
+        let snapshot = unsafe { Pin::new_unchecked(snapshot) };
+
+        ReadPinned { snapshot }
+    }
+
+    /// Replace the value, returning the pinned old value, see [`Rcu::replace`]
+    pub fn replace(&self, new_value: T) -> Pin<Arc<T>> {
+        let old_value = self.arcu.replace(new_value);
+
+        // Safety:
+        // - the value has been pinned when it was put into the PinnedArcu
+        unsafe { Pin::new_unchecked(old_value) }
+    }
+}
+
+#[cfg(feature = "thread_local_counter")]
+impl<T> PinnedArcu<T, crate::epoch_counters::GlobalEpochCounterPool> {
+    /// Read the value of the Rcu for the current epoch, see [`Rcu::read`]
+    pub fn read_pin(&self) -> ReadPinned<T> {
+        crate::epoch_counters::with_thread_local_epoch_counter(|epoch_counter| {
+            // Safety:
+            // - we just registered the epoch counter
+            // - this is a thread local epoch counter that is only used here, so there can't be a concurrent use
+            unsafe { self.raw_read_pin(epoch_counter) }
+        })
+    }
+
+    /// Read the value of the Rcu as a pinned snapshot, see [`PinnedArcu::read_pin`]
+    ///
+    /// The snapshot keeps the value alive independent of the Rcu, e.g. for async tasks polling futures stored in the Rcu.
+    /// There is no equivalent for a plain [`Arcu`], as its values may be moved out of the `Arc`s it hands out.
+    pub fn read_pinned_arc(&self) -> Pin<Arc<T>> {
+        self.read_pin().into_pin_arc()
+    }
+}
+
+/// A snapshot of the value of a [`PinnedArcu`]
+pub struct ReadPinned<T> {
+    snapshot: Pin<Arc<T>>,
+}
+
+impl<T> ReadPinned<T> {
+    /// Get a pinned reference to the value
+    pub fn get(&self) -> Pin<&T> {
+        self.snapshot.as_ref()
+    }
+
+    /// Get the pinned snapshot, keeping the value alive independent of this guard
+    pub fn into_pin_arc(self) -> Pin<Arc<T>> {
+        self.snapshot
+    }
+}
+
+impl<T> Deref for ReadPinned<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.snapshot
+    }
+}
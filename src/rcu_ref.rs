@@ -13,6 +13,8 @@ where
     #[allow(dead_code)]
     arc: Arc<T>,
     data: NonNull<M>,
+    #[cfg(feature = "debug_snapshots")]
+    holder: SnapshotHolder,
 }
 
 impl<T: ?Sized, M: ?Sized + Debug> Debug for RcuRef<T, M> {
@@ -28,6 +30,8 @@ impl<T: ?Sized> RcuRef<T, T> {
     pub fn new(arc: Arc<T>) -> Self {
         Self {
             data: arc.as_ref().into(),
+            #[cfg(feature = "debug_snapshots")]
+            holder: SnapshotHolder::new(&arc),
             arc,
         }
     }
@@ -45,6 +49,8 @@ impl<T: ?Sized, M: ?Sized> RcuRef<T, M> {
             arc: reference.arc,
             // Safety: See deref
             data: f(unsafe { reference.data.as_ref() }).into(),
+            #[cfg(feature = "debug_snapshots")]
+            holder: reference.holder,
         }
     }
 
@@ -56,16 +62,91 @@ impl<T: ?Sized, M: ?Sized> RcuRef<T, M> {
         // Safety: See deref
         let val = f(unsafe { reference.data.as_ref() })?;
         Some(RcuRef {
-            arc: Arc::clone(&reference.arc),
             data: val.into(),
+            arc: reference.arc,
+            #[cfg(feature = "debug_snapshots")]
+            holder: reference.holder,
         })
     }
 
+    /// try to apply the faillable mapping function to the reference in this RcuRef,
+    /// handing back the unchanged RcuRef when it fails, e.g. to try a different projection
+    pub fn try_map_or_self<N: ?Sized, F: for<'a> FnOnce(&'a M) -> Option<&'a N>>(
+        reference: Self,
+        f: F,
+    ) -> Result<RcuRef<T, N>, Self> {
+        // Safety: See deref
+        match f(unsafe { reference.data.as_ref() }) {
+            Some(val) => Ok(RcuRef {
+                data: val.into(),
+                arc: reference.arc,
+                #[cfg(feature = "debug_snapshots")]
+                holder: reference.holder,
+            }),
+            None => Err(reference),
+        }
+    }
+
+    /// apply the mapping function to split the reference in this RcuRef into two, e.g. to reference two fields
+    ///
+    /// Both RcuRefs keep the value alive, see [`core::cell::Ref::map_split`]
+    pub fn map_split<A: ?Sized, B: ?Sized, F: for<'a> FnOnce(&'a M) -> (&'a A, &'a B)>(
+        reference: Self,
+        f: F,
+    ) -> (RcuRef<T, A>, RcuRef<T, B>) {
+        // Safety: See deref
+        let (a, b) = f(unsafe { reference.data.as_ref() });
+        let (a, b) = (a.into(), b.into());
+        let second = RcuRef {
+            data: b,
+            arc: Arc::clone(&reference.arc),
+            #[cfg(feature = "debug_snapshots")]
+            holder: SnapshotHolder::new(&reference.arc),
+        };
+        let first = RcuRef {
+            data: a,
+            arc: reference.arc,
+            #[cfg(feature = "debug_snapshots")]
+            holder: reference.holder,
+        };
+        (first, second)
+    }
+
+    /// Project to the element at `index` of the referenced slice, vec or array
+    ///
+    /// Returns `None` when `index` is out of bounds
+    pub fn get<U>(this: Self, index: usize) -> Option<RcuRef<T, U>>
+    where
+        M: AsRef<[U]>,
+    {
+        Self::try_map(this, |slice| slice.as_ref().get(index))
+    }
+
+    /// Iterate over the elements of the referenced slice, vec or array
+    ///
+    /// The iterator keeps the snapshot alive, so it isn't affected by later writes to the Rcu
+    pub fn iter<U>(this: Self) -> RcuIter<T, U>
+    where
+        M: AsRef<[U]>,
+    {
+        RcuIter {
+            slice: Self::map(this, |elements| elements.as_ref()),
+            index: 0,
+        }
+    }
+
     /// Check whether the two RcuRefs reference values in the same epoch
-    pub fn same_epoch<M2>(this: &Self, other: &RcuRef<T, M2>) -> bool {
+    pub fn same_epoch<M2: ?Sized>(this: &Self, other: &RcuRef<T, M2>) -> bool {
         Arc::ptr_eq(&this.arc, &other.arc)
     }
 
+    /// Check whether the RcuRef references a different epoch than the previous one, i.e. the Rcu has been written to in between
+    ///
+    /// A write may replace a value with an equal one, see [`RcuRef::value_changed_from`] to compare the values instead.
+    pub fn changed_from<M2: ?Sized>(this: &Self, previous: &RcuRef<T, M2>) -> bool {
+        !Self::same_epoch(this, previous)
+    }
+
     /// Compares the RcuRefs references via [`core::ptr::eq`]
     pub fn ptr_eq(this: &Self, other: &Self) -> bool {
         core::ptr::eq(this.data.as_ptr(), other.data.as_ptr())
@@ -84,9 +165,16 @@ impl<T: ?Sized, M: ?Sized> RcuRef<T, M> {
         Self {
             arc: Arc::clone(&this.arc),
             data: this.data,
+            #[cfg(feature = "debug_snapshots")]
+            holder: SnapshotHolder::new(&this.arc),
         }
     }
 
+    /// Get the arc of the value that was stored in the Rcu, dropping any mappings
+    pub fn into_arc(this: Self) -> Arc<T> {
+        this.arc
+    }
+
     /// Get a reference to root of the RcuRef
     ///
     /// i.e. the value that was stored in the Rcu
@@ -96,6 +184,56 @@ impl<T: ?Sized, M: ?Sized> RcuRef<T, M> {
     }
 }
 
+impl<T: ?Sized, M: ?Sized + PartialEq> RcuRef<T, M> {
+    /// Check whether the referenced value differs from the previous one
+    ///
+    /// Unlike [`RcuRef::changed_from`] this ignores writes that didn't change the referenced value,
+    /// e.g. writes replacing the value with an equal one or only changing parts the RcuRefs aren't projected to.
+    pub fn value_changed_from(this: &Self, previous: &Self) -> bool {
+        Self::changed_from(this, previous) && **this != **previous
+    }
+}
+
+/// Compares the referenced values, not the epochs
+///
+/// Two RcuRefs from different epochs are equal when they reference equal values,
+/// see [`RcuRef::same_epoch`] and [`RcuRef::ptr_eq`] to check their identity instead.
+impl<T: ?Sized, M: ?Sized + PartialEq> PartialEq for RcuRef<T, M> {
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl<T: ?Sized, M: ?Sized + Eq> Eq for RcuRef<T, M> {}
+
+/// Compares the referenced values, like [`PartialEq`]
+impl<T: ?Sized, M: ?Sized + PartialOrd> PartialOrd for RcuRef<T, M> {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        M::partial_cmp(self, other)
+    }
+}
+
+impl<T: ?Sized, M: ?Sized + Ord> Ord for RcuRef<T, M> {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        M::cmp(self, other)
+    }
+}
+
+/// Hashes the referenced value, consistent with [`PartialEq`]
+impl<T: ?Sized, M: ?Sized + core::hash::Hash> core::hash::Hash for RcuRef<T, M> {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        M::hash(self, state)
+    }
+}
+
+/// Serializes the referenced value transparently
+#[cfg(feature = "serde")]
+impl<T: ?Sized, M: ?Sized + serde::Serialize> serde::Serialize for RcuRef<T, M> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        M::serialize(self, serializer)
+    }
+}
+
 impl<T: ?Sized, M: ?Sized> Deref for RcuRef<T, M> {
     type Target = M;
 
@@ -106,3 +244,139 @@ impl<T: ?Sized, M: ?Sized> Deref for RcuRef<T, M> {
         unsafe { self.data.as_ref() }
     }
 }
+
+// the threads holding RcuRefs, by the address of the value the RcuRefs keep alive
+#[cfg(feature = "debug_snapshots")]
+static SNAPSHOT_HOLDERS: std::sync::Mutex<
+    alloc::collections::BTreeMap<usize, Vec<std::thread::ThreadId>>,
+> = std::sync::Mutex::new(alloc::collections::BTreeMap::new());
+
+/// The threads holding [`RcuRef`]s, by the address of the value they keep alive
+///
+/// Each `RcuRef` is listed with the thread that created it, which is the thread holding it as `RcuRef`s can't be sent.
+/// Values held by multiple `RcuRef`s list the thread once per `RcuRef`.
+/// Addresses that are no longer the current value of their Rcu belong to replaced values kept alive by these threads,
+/// e.g. to find the thread holding on to an old configuration. Snapshots held in other ways, e.g. as `Arc`, aren't listed.
+#[cfg(feature = "debug_snapshots")]
+pub fn snapshot_holders() -> Vec<(usize, Vec<std::thread::ThreadId>)> {
+    SNAPSHOT_HOLDERS
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .iter()
+        .map(|(addr, threads)| (*addr, threads.clone()))
+        .collect()
+}
+
+// registers the current thread as holding the value in SNAPSHOT_HOLDERS until dropped
+#[cfg(feature = "debug_snapshots")]
+struct SnapshotHolder {
+    addr: usize,
+    thread: std::thread::ThreadId,
+}
+
+#[cfg(feature = "debug_snapshots")]
+impl SnapshotHolder {
+    fn new<T: ?Sized>(arc: &Arc<T>) -> Self {
+        let holder = Self {
+            addr: Arc::as_ptr(arc).cast::<()>() as usize,
+            thread: std::thread::current().id(),
+        };
+        SNAPSHOT_HOLDERS
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .entry(holder.addr)
+            .or_default()
+            .push(holder.thread);
+        holder
+    }
+}
+
+#[cfg(feature = "debug_snapshots")]
+impl Drop for SnapshotHolder {
+    fn drop(&mut self) {
+        let mut holders = SNAPSHOT_HOLDERS
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        if let Some(threads) = holders.get_mut(&self.addr) {
+            if let Some(index) = threads.iter().position(|thread| *thread == self.thread) {
+                threads.swap_remove(index);
+            }
+            if threads.is_empty() {
+                holders.remove(&self.addr);
+            }
+        }
+    }
+}
+
+/// An iterator over the elements of a slice in an [`RcuRef`], see [`RcuRef::iter`]
+pub struct RcuIter<T: ?Sized, U> {
+    slice: RcuRef<T, [U]>,
+    index: usize,
+}
+
+impl<T: ?Sized, U> Iterator for RcuIter<T, U> {
+    type Item = RcuRef<T, U>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let element = RcuRef::get(RcuRef::clone(&self.slice), self.index)?;
+        self.index += 1;
+        Some(element)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.slice.len() - self.index;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<T: ?Sized, U> ExactSizeIterator for RcuIter<T, U> {}
+
+impl<T: ?Sized, U> core::iter::FusedIterator for RcuIter<T, U> {}
+
+// Safety:
+// the target points into the arc we are holding, which doesn't move while we are alive
+//
+// CloneStableDeref is not implemented as RcuRef doesn't implement Clone, see RcuRef::clone
+#[cfg(feature = "stable_deref")]
+unsafe impl<T: ?Sized, M: ?Sized> stable_deref_trait::StableDeref for RcuRef<T, M> {}
+
+/// A snapshot of the content of an [`super::Rcu`] implementing [`Clone`], see [`super::Rcu::snapshot`]
+///
+/// [`RcuRef`] only provides [`RcuRef::clone`] as an associated function to not shadow the `clone` of the referenced value,
+/// so it doesn't satisfy `Clone` bounds, e.g. of extractors or shared state in web frameworks.
+/// This snapshot implements the [`Clone`] trait and can be used there instead,
+/// at the cost of not supporting projections. Cloning the referenced value requires `T::clone(&snapshot)`.
+pub struct SharedSnapshot<T: ?Sized>(Arc<T>);
+
+impl<T: ?Sized> SharedSnapshot<T> {
+    /// Get the arc of the snapshot
+    pub fn into_arc(this: Self) -> Arc<T> {
+        this.0
+    }
+}
+
+impl<T: ?Sized> From<Arc<T>> for SharedSnapshot<T> {
+    fn from(arc: Arc<T>) -> Self {
+        Self(arc)
+    }
+}
+
+impl<T: ?Sized> Clone for SharedSnapshot<T> {
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+impl<T: ?Sized> Deref for SharedSnapshot<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T: ?Sized + Debug> Debug for SharedSnapshot<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("SharedSnapshot").field(&&*self.0).finish()
+    }
+}
@@ -0,0 +1,171 @@
+//! Handling replaced values whose reclamation could not be confirmed
+
+use alloc::sync::Arc;
+use core::mem::ManuallyDrop;
+
+use crate::arc_like::{ArcLike, ThinPtr};
+use crate::epoch_counters::{EpochCounterPool, WaitError};
+
+/// The error returned by [`crate::Rcu::try_replace`] when the pool gave up waiting for the epochs
+///
+/// The new value has been swapped in, but readers may still be about to increment the strong count of the old value,
+/// so it must not be dropped yet. The error holds on to the old value, which can be
+/// - retried with [`ReclaimError::retry`] or waited for with [`ReclaimError::wait`], or
+/// - leaked with [`ReclaimError::leak`], which is also what dropping the error does
+pub struct ReclaimError<'a, T: ?Sized> {
+    old_value: ManuallyDrop<Unreclaimed<Arc<T>>>,
+    epoch_counter_pool: &'a dyn EpochCounterPool,
+    cause: WaitError,
+}
+
+impl<'a, T: ?Sized> ReclaimError<'a, T> {
+    /// ## Safety
+    /// - `old_value` must become reclaimable once the `epoch_counter_pool` has been waited for
+    #[cfg(target_has_atomic = "ptr")]
+    pub(crate) unsafe fn new(
+        old_value: Unreclaimed<Arc<T>>,
+        epoch_counter_pool: &'a dyn EpochCounterPool,
+        cause: WaitError,
+    ) -> Self {
+        Self {
+            old_value: ManuallyDrop::new(old_value),
+            epoch_counter_pool,
+            cause,
+        }
+    }
+
+    /// The reason the pool gave up waiting
+    pub fn cause(&self) -> WaitError {
+        self.cause
+    }
+
+    /// Try waiting for the epochs again, see [`EpochCounterPool::try_wait_for_epochs`]
+    ///
+    /// Returns the old value once it is reclaimable.
+    pub fn retry(mut self) -> Result<Arc<T>, Self> {
+        match self.epoch_counter_pool.try_wait_for_epochs() {
+            // Safety:
+            // - the epochs have been waited for
+            // - self is dropped afterwards and doesn't use the old value again
+            Ok(()) => Ok(unsafe { ManuallyDrop::take(&mut self.old_value) }.into_inner()),
+            Err(cause) => {
+                self.cause = cause;
+                Err(self)
+            }
+        }
+    }
+
+    /// Block until the old value is reclaimable, see [`EpochCounterPool::wait_for_epochs`]
+    pub fn wait(mut self) -> Arc<T> {
+        self.epoch_counter_pool.wait_for_epochs();
+        // Safety:
+        // - the epochs have been waited for
+        // - self is dropped afterwards and doesn't use the old value again
+        unsafe { ManuallyDrop::take(&mut self.old_value) }.into_inner()
+    }
+
+    /// Give up on the old value, it will never be dropped
+    pub fn leak(self) {}
+}
+
+impl<T: ?Sized> core::fmt::Debug for ReclaimError<'_, T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("ReclaimError")
+            .field("cause", &self.cause)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T: ?Sized> core::fmt::Display for ReclaimError<'_, T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "the replaced value could not be reclaimed: {}",
+            self.cause
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: ?Sized> std::error::Error for ReclaimError<'_, T> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.cause)
+    }
+}
+
+/// A value replaced in an [`crate::atomic::Arcu`] that readers may still be about to access
+///
+/// This keeps the box of values with boxed thin pointers alive together with the value, see [`ThinPtr`],
+/// as readers may still load the pointer from it. Like the value itself, it must not be dropped before the epochs have been witnessed.
+pub(crate) struct Unreclaimed<A> {
+    value: ManuallyDrop<A>,
+    thin_box: Option<ThinBox>,
+}
+
+// the boxed thin pointer of a value, see ThinPtr::BOXED
+struct ThinBox {
+    thin: *mut (),
+    // ThinPtr::from_thin of the value's type
+    free_thin: unsafe fn(*mut ()),
+}
+
+// Safety:
+// the box only holds a pointer to the value, so it can be sent and shared like the value
+unsafe impl<A: Send> Send for Unreclaimed<A> {}
+// Safety:
+// the box is only accessed when dropping
+unsafe impl<A: Sync> Sync for Unreclaimed<A> {}
+
+impl<A: ArcLike> Unreclaimed<A>
+where
+    A::Target: ThinPtr,
+{
+    /// ## Safety
+    /// - `thin` must have been created by [`ThinPtr::into_thin`] from a pointer returned by [`ArcLike::into_raw`]
+    /// - the caller gives up the thin pointer and one strong count
+    #[cfg(target_has_atomic = "ptr")]
+    pub(crate) unsafe fn from_thin(thin: *mut ()) -> Self {
+        /// ## Safety
+        /// - see [`ThinPtr::from_thin`]
+        unsafe fn free_thin<T: ?Sized + ThinPtr>(thin: *mut ()) {
+            // Safety: the caller upholds the safety requirements
+            unsafe { T::from_thin(thin) };
+        }
+
+        Self {
+            // Safety: the caller upholds the safety requirements
+            value: ManuallyDrop::new(unsafe { A::from_raw(A::Target::load_thin(thin)) }),
+            thin_box: A::Target::BOXED.then_some(ThinBox {
+                thin,
+                free_thin: free_thin::<A::Target>,
+            }),
+        }
+    }
+}
+
+impl<A> Unreclaimed<A> {
+    /// Get the value, dropping the box
+    ///
+    /// Like dropping, this must only be called once the epochs have been witnessed
+    pub(crate) fn into_inner(self) -> A {
+        let mut this = ManuallyDrop::new(self);
+        this.free_box();
+        // Safety: this is never used or dropped again
+        unsafe { ManuallyDrop::take(&mut this.value) }
+    }
+
+    fn free_box(&mut self) {
+        if let Some(ThinBox { thin, free_thin }) = self.thin_box.take() {
+            // Safety: the thin pointer has been created for A::Target, whose from_thin is free_thin
+            unsafe { free_thin(thin) }
+        }
+    }
+}
+
+impl<A> Drop for Unreclaimed<A> {
+    fn drop(&mut self) {
+        self.free_box();
+        // Safety: the value is never used again
+        unsafe { ManuallyDrop::drop(&mut self.value) }
+    }
+}
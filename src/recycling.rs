@@ -0,0 +1,73 @@
+//! This module contains [`RecyclingArcu`], an [`Arcu`] reusing the allocations of replaced values
+
+use alloc::sync::Arc;
+use std::sync::Mutex;
+
+use crate::{atomic::Arcu, epoch_counters::EpochCounterPool, Rcu};
+
+/// An [`Arcu`] that recycles the allocations of replaced values for new values
+///
+/// When a replaced value is unique once it has been reclaimed, i.e. no reader still holds a snapshot of it,
+/// its allocation is kept in a small free list and reused by a later [`RecyclingArcu::replace_recycling`],
+/// instead of freeing it and allocating a new one.
+///
+/// This only helps when replaced values are typically unique once reclaimed,
+/// i.e. readers don't keep their snapshots around for long.
+/// Values in the free list are only dropped once their allocation is reused or the `RecyclingArcu` is dropped,
+/// so this is best suited for values not holding on to other resources.
+pub struct RecyclingArcu<T, P> {
+    arcu: Arcu<T, P>,
+    free_list: Mutex<Vec<Arc<T>>>,
+    capacity: usize,
+}
+
+impl<T, P: EpochCounterPool> RecyclingArcu<T, P> {
+    /// Create a new RecyclingArcu keeping up to `capacity` allocations for reuse
+    pub fn new(initial: impl Into<Arc<T>>, epoch_counter_pool: P, capacity: usize) -> Self {
+        Self {
+            arcu: Arcu::new(initial, epoch_counter_pool),
+            free_list: Mutex::new(Vec::with_capacity(capacity)),
+            capacity,
+        }
+    }
+
+    /// Get the underlying Arcu, e.g. to read from it
+    ///
+    /// Writes to the Arcu are fine, but don't recycle allocations.
+    pub fn arcu(&self) -> &Arcu<T, P> {
+        &self.arcu
+    }
+
+    /// Replace the value, reusing a recycled allocation if available
+    ///
+    /// Like [`Rcu::replace`] this blocks until the old value can be reclaimed,
+    /// after which it is recycled if it is unique.
+    /// Unlike [`Rcu::replace`] the old value isn't returned, as it is kept for recycling or dropped,
+    /// use [`RecyclingArcu::arcu`] to replace it without recycling.
+    pub fn replace_recycling(&self, new_value: T) {
+        let recycled = self.free_list.lock().unwrap().pop();
+
+        let new_value = match recycled {
+            Some(mut recycled) => {
+                // only unique arcs are put into the free list and they are never shared from there
+                *Arc::get_mut(&mut recycled).expect("recycled arcs are unique") = new_value;
+                recycled
+            }
+            None => Arc::new(new_value),
+        };
+
+        let mut old_value = self.arcu.replace(new_value);
+
+        if Arc::get_mut(&mut old_value).is_some() {
+            let mut free_list = self.free_list.lock().unwrap();
+            if free_list.len() < self.capacity {
+                free_list.push(old_value);
+            }
+        }
+    }
+
+    /// The number of allocations currently available for reuse
+    pub fn recycled(&self) -> usize {
+        self.free_list.lock().unwrap().len()
+    }
+}
@@ -4,11 +4,17 @@
 
 extern crate alloc;
 
-use std::{marker::PhantomData, sync::RwLock};
+use std::{
+    marker::PhantomData,
+    sync::{PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard},
+};
 
 use alloc::sync::Arc;
+#[cfg(feature = "stats")]
+use core::sync::atomic::{AtomicUsize, Ordering};
 
 use crate::epoch_counters::{EpochCounter, EpochCounterPool};
+use crate::rcu_ref::RcuRef;
 
 use super::Rcu;
 
@@ -18,27 +24,185 @@ use super::Rcu;
 ///
 /// This Rcu uses a RwLocks for synchronization instead of the EpochCounterPool.
 /// The EpochCounterPool is kept to keep the API compatible with the atomics based one.
-pub struct Arcu<T, P> {
+///
+/// The lock is never held for writing while calling user code, except by [`Arcu::update_in_place`],
+/// so it only becomes poisoned by a panic there.
+/// Should it be poisoned, the Arcu keeps working like the atomics based one, but the value may be partially updated,
+/// see [`Arcu::is_poisoned`].
+///
+/// The poison is permanent: clearing it requires [`RwLock::clear_poison`], which is newer than the minimum supported Rust version.
+/// So once poisoned, [`Arcu::is_poisoned`] and [`Arcu::try_read_checked`] report it until the Arcu is dropped,
+/// even after a later write replaced the partially updated value.
+pub struct Arcu<T: ?Sized, P> {
     active_value: RwLock<Arc<T>>,
+    #[cfg(feature = "stats")]
+    conflicts: AtomicUsize,
     epoch_counter_pool: PhantomData<P>,
 }
 
-impl<T: core::fmt::Display, P> core::fmt::Display for Arcu<T, P> {
+impl<T: core::fmt::Display + ?Sized, P> core::fmt::Display for Arcu<T, P> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        T::fmt(&self.active_value.read().unwrap(), f)
+        T::fmt(&self.read_lock(), f)
     }
 }
 
-impl<T: core::fmt::Debug, P> core::fmt::Debug for Arcu<T, P> {
+impl<T: core::fmt::Debug + ?Sized, P> core::fmt::Debug for Arcu<T, P> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.debug_struct("Rcu")
-            .field("active_value", &self.active_value.read().unwrap())
+            .field("active_value", &self.read_lock())
             .field("epoch_counter_pool", &"Opaque")
             .finish()
     }
 }
 
-impl<T, P: EpochCounterPool> Rcu for Arcu<T, P> {
+/// Serializes the current value transparently
+///
+/// The value is read once, writes replacing it while it is serialized are not included.
+#[cfg(feature = "serde")]
+impl<T: ?Sized + serde::Serialize, P> serde::Serialize for Arcu<T, P> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let value = self.read_lock().clone();
+        T::serialize(&value, serializer)
+    }
+}
+
+/// Deserializes the initial value of a new Arcu with the default pool
+#[cfg(feature = "serde")]
+impl<'de, T: serde::Deserialize<'de>, P: EpochCounterPool + Default> serde::Deserialize<'de>
+    for Arcu<T, P>
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        T::deserialize(deserializer).map(|initial| Self::new(initial, P::default()))
+    }
+}
+
+impl<T: ?Sized, P> From<Arcu<T, P>> for Arc<T> {
+    /// Consume the Arcu, returning the active value
+    #[inline]
+    fn from(rcu: Arcu<T, P>) -> Self {
+        rcu.into_inner()
+    }
+}
+
+// safe versions of the Rcu methods that work with any pool,
+// so that the Rcu can be used without unsafe and without the thread local epoch counter
+impl<T: ?Sized, P> Arcu<T, P> {
+    /// Whether the lock has been poisoned by a panic while it was held
+    ///
+    /// A poisoned lock is otherwise ignored, reads and writes keep working.
+    /// The active value may have been left partially updated by [`Arcu::update_in_place`],
+    /// and stays poisoned even once it has been replaced, see [`Arcu`].
+    pub fn is_poisoned(&self) -> bool {
+        self.active_value.is_poisoned()
+    }
+
+    /// Consume the Arcu, returning the active value
+    ///
+    /// As the Arcu is owned there can't be any readers, so this doesn't wait.
+    pub fn into_inner(self) -> Arc<T> {
+        self.active_value
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Get mutable access to the active value, if there are no other references to it, see [`Arc::get_mut`]
+    pub fn get_mut(&mut self) -> Option<&mut T> {
+        Arc::get_mut(
+            self.active_value
+                .get_mut()
+                .unwrap_or_else(PoisonError::into_inner),
+        )
+    }
+
+    fn read_lock(&self) -> RwLockReadGuard<'_, Arc<T>> {
+        self.active_value
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn write_lock(&self) -> RwLockWriteGuard<'_, Arc<T>> {
+        self.active_value
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// The number of times [`Arcu::try_update`] had to retry, as the value was replaced in between
+    #[cfg(feature = "stats")]
+    pub fn conflict_count(&self) -> usize {
+        self.conflicts.load(Ordering::Relaxed)
+    }
+
+    /// Read the value of the Rcu, see [`Rcu::read`]
+    #[inline]
+    pub fn read(&self) -> RcuRef<T, T> {
+        RcuRef::new(self.read_lock().clone())
+    }
+
+    /// Replace the Rcu's content with a new value, see [`Rcu::replace`]
+    #[inline]
+    pub fn replace(&self, new_value: impl Into<Arc<T>>) -> Arc<T> {
+        // convert before locking, the conversion is user code that may panic
+        let new_value = new_value.into();
+        std::mem::replace(&mut self.write_lock(), new_value)
+    }
+
+    /// Read the value of the Rcu, failing when the lock has been poisoned, see [`Rcu::try_read_checked`]
+    pub fn try_read_checked(&self) -> Result<RcuRef<T, T>, crate::BackendError> {
+        if self.is_poisoned() {
+            return Err(crate::BackendError::Poisoned);
+        }
+        Ok(self.read())
+    }
+
+    /// Update the value in place while holding the write lock, cloning it first only when readers hold snapshots of it
+    ///
+    /// Unlike [`Arcu::try_update`] this doesn't clone the value when the Arcu holds the only reference to it,
+    /// see [`Arc::make_mut`]. Readers are blocked while `update` runs, so it should be kept short.
+    ///
+    /// ## Panics
+    /// A panic in `update` or in [`Clone::clone`] poisons the lock for good, see [`Arcu::is_poisoned`].
+    /// `update` works on the active value itself rather than a private copy,
+    /// so the changes made before the panic stay and later reads see the partially updated value.
+    /// Use [`Arcu::try_update`] when `update` may panic, which only installs fully updated values.
+    pub fn update_in_place(&self, update: impl FnOnce(&mut T))
+    where
+        T: Clone,
+    {
+        update(Arc::make_mut(&mut self.write_lock()))
+    }
+
+    /// Update the Rcu using the provided update function, see [`Rcu::try_update`]
+    pub fn try_update<F, R>(&self, mut update: F) -> Option<Arc<T>>
+    where
+        F: FnMut(&T) -> Option<R>,
+        R: Into<Arc<T>>,
+    {
+        self.try_update_indexed(|old, _| update(old).map(Into::into))
+    }
+
+    /// Update the Rcu using the provided update function, see [`Rcu::try_update_indexed`]
+    ///
+    /// Each failed attempt is due to a different writer, as comparing under the lock never fails spuriously.
+    pub fn try_update_indexed(
+        &self,
+        mut update: impl FnMut(&T, usize) -> Option<Arc<T>>,
+    ) -> Option<Arc<T>> {
+        let mut failed = 0;
+        loop {
+            let old = self.read_lock().clone();
+            let new = update(&old, failed)?;
+            let mut cur = self.write_lock();
+            if Arc::ptr_eq(&cur, &old) {
+                return Some(std::mem::replace(&mut cur, new));
+            }
+            failed += 1;
+            #[cfg(feature = "stats")]
+            self.conflicts.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+impl<T: ?Sized, P: EpochCounterPool> Rcu for Arcu<T, P> {
     type Item = T;
     type Pool = P;
 
@@ -47,20 +211,34 @@ impl<T, P: EpochCounterPool> Rcu for Arcu<T, P> {
         Arcu {
             // active_value: AtomicPtr::new(Arc::into_raw(initial.into()).cast_mut()),
             active_value: RwLock::new(initial.into()),
+            #[cfg(feature = "stats")]
+            conflicts: AtomicUsize::new(0),
             epoch_counter_pool: PhantomData,
         }
     }
 
     /// ## Safety
     /// - this impl is actually safe
-    #[inline]
+    #[cfg_attr(not(feature = "profiling"), inline)]
+    #[cfg_attr(feature = "profiling", inline(never))]
     unsafe fn raw_read(&self, _epoch_counter: &EpochCounter) -> Arc<T> {
-        self.active_value.read().unwrap().clone()
+        self.read_lock().clone()
+    }
+
+    #[cfg(feature = "thread_local_counter")]
+    fn try_read_checked(&self) -> Result<RcuRef<Self::Item, Self::Item>, crate::BackendError>
+    where
+        Self: Rcu<Pool = crate::epoch_counters::GlobalEpochCounterPool>,
+    {
+        if self.is_poisoned() {
+            return Err(crate::BackendError::Poisoned);
+        }
+        Ok(Rcu::read(self))
     }
 
     #[inline]
     fn replace(&self, new_value: impl Into<Arc<T>>) -> Arc<T> {
-        std::mem::replace(&mut self.active_value.write().unwrap(), new_value.into())
+        Arcu::replace(self, new_value)
     }
 
     /// Update the Rcu using the provided update function
@@ -69,21 +247,23 @@ impl<T, P: EpochCounterPool> Rcu for Arcu<T, P> {
     ///
     /// ## Safety
     /// - this impl is actually safe
-    #[inline]
+    #[cfg_attr(not(feature = "profiling"), inline)]
+    #[cfg_attr(feature = "profiling", inline(never))]
     unsafe fn raw_try_update<'a>(
         &self,
-        mut update: impl FnMut(&T) -> Option<Arc<T>>,
+        update: impl FnMut(&T) -> Option<Arc<T>>,
         _epoch_counter: &EpochCounter,
     ) -> Option<Arc<T>> {
-        loop {
-            let old = self.active_value.read().unwrap().clone();
-            let new = update(&old)?;
-            let mut cur = self.active_value.write().unwrap();
-            if Arc::ptr_eq(&cur, &old) {
-                return Some(std::mem::replace(&mut cur, new));
-            } else {
-                println!("Ptr neq, retry!")
-            }
-        }
+        Arcu::try_update(self, update)
+    }
+
+    /// ## Safety
+    /// - this impl is actually safe
+    unsafe fn raw_try_update_indexed(
+        &self,
+        update: impl FnMut(&T, usize) -> Option<Arc<T>>,
+        _epoch_counter: &EpochCounter,
+    ) -> Option<Arc<T>> {
+        Arcu::try_update_indexed(self, update)
     }
 }
@@ -0,0 +1,65 @@
+//! This module contains [`ScopedReader`], a reader whose epoch counter is bound to a [`std::thread::Scope`]
+
+use alloc::sync::Arc;
+use core::{cell::Cell, marker::PhantomData};
+use std::thread::Scope;
+
+use crate::{
+    epoch_counters::{
+        register_epoch_counter, unregister_epoch_counter, EpochCounter, GlobalEpochCounterPool,
+    },
+    rcu_ref::RcuRef,
+    Rcu,
+};
+
+/// A reader with its own epoch counter, that is unregistered when the reader is dropped, at the latest when the scope ends
+///
+/// [`Rcu::read`] registers a thread local epoch counter on each thread's first read, which stays registered until the thread exits.
+/// For threads that keep running after their work is done, e.g. the threads of a pool,
+/// a `ScopedReader` unregisters its epoch counter deterministically instead, so the global counters don't keep growing.
+///
+/// The epoch counter is registered with the [`GlobalEpochCounterPool`] when creating the reader.
+/// A `ScopedReader` can be moved to another thread, but not shared between threads,
+/// as its epoch counter must not be used concurrently.
+pub struct ScopedReader<'scope> {
+    epoch_counter: Arc<EpochCounter>,
+    scope: PhantomData<&'scope ()>,
+    not_sync: PhantomData<Cell<()>>,
+}
+
+impl<'scope> ScopedReader<'scope> {
+    /// Create a new reader that can't outlive the scope, registering its epoch counter
+    pub fn new<'env>(_scope: &'scope Scope<'scope, 'env>) -> Self {
+        let epoch_counter = Arc::new(EpochCounter::new());
+        register_epoch_counter(Arc::downgrade(&epoch_counter));
+        Self {
+            epoch_counter,
+            scope: PhantomData,
+            not_sync: PhantomData,
+        }
+    }
+
+    /// Read the value of the Rcu using this reader's epoch counter, see [`Rcu::read`]
+    pub fn read<R>(&self, rcu: &R) -> RcuRef<R::Item, R::Item>
+    where
+        R: Rcu<Pool = GlobalEpochCounterPool>,
+    {
+        // Safety:
+        // - the epoch counter has been registered with the global pool when creating the reader
+        // - the reader isn't Sync and doesn't give out its epoch counter, so there can't be a concurrent use
+        RcuRef::new(unsafe { rcu.raw_read(&self.epoch_counter) })
+    }
+}
+
+impl Drop for ScopedReader<'_> {
+    fn drop(&mut self) {
+        // no read is in progress, as they borrow the reader
+        unregister_epoch_counter(&self.epoch_counter);
+    }
+}
+
+impl core::fmt::Debug for ScopedReader<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("ScopedReader").finish_non_exhaustive()
+    }
+}
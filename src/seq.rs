@@ -0,0 +1,140 @@
+//! This module contains [`SeqArcu`], an [`Arcu`] whose values are paired with a seqlock-style sequence number
+
+use alloc::sync::Arc;
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use crate::{
+    atomic::Arcu,
+    epoch_counters::{AdaptiveBackoff, Backoff, EpochCounter, EpochCounterPool},
+    Rcu,
+};
+
+/// An [`Arcu`] pairing each value with a sequence number, read together as a consistent pair
+///
+/// The sequence number counts the writes, so metadata that must stay consistent with the value,
+/// e.g. the sequence number of an external log the value was built from, can be derived from it.
+///
+/// ## Consistency
+/// The sequence works like the one of a seqlock: a write makes it odd, swaps the pointer and makes it even again.
+/// Writers wait for each other while it is odd, so only one write is in progress at a time.
+/// A read retries while a write is in progress or when the sequence changed around loading the pointer,
+/// so a read torn by a concurrent write is never returned.
+///
+/// Unlike [`Arcu::raw_read_with_generation`], which also tolerates concurrent writers,
+/// this serializes the writers so that [`SeqArcu::replace`] can return the sequence number of the value it wrote.
+pub struct SeqArcu<T, P> {
+    arcu: Arcu<T, P>,
+    // twice the number of writes, odd while a write is in progress
+    sequence: AtomicU64,
+}
+
+impl<T, P: EpochCounterPool> SeqArcu<T, P> {
+    /// Create a new SeqArcu with the given initial value at sequence number zero
+    pub fn new(initial: impl Into<Arc<T>>, epoch_counter_pool: P) -> Self {
+        Self {
+            arcu: Arcu::new(initial, epoch_counter_pool),
+            sequence: AtomicU64::new(0),
+        }
+    }
+
+    /// Read the value together with its sequence number
+    ///
+    /// Retries until no write was in progress and the sequence was stable around loading the value.
+    ///
+    /// ## Safety
+    /// - The epoch counter must not be used concurrently
+    /// - The epoch counter must belong to the EpochCounterPool of this Rcu
+    pub unsafe fn raw_read(&self, epoch_counter: &EpochCounter) -> (Arc<T>, u64) {
+        let mut backoff = AdaptiveBackoff::default();
+        loop {
+            let before = self.sequence.load(Ordering::SeqCst);
+            if before % 2 == 0 {
+                // Safety: the caller upholds the safety requirements
+                let value = unsafe { self.arcu.raw_read(epoch_counter) };
+                if self.sequence.load(Ordering::SeqCst) == before {
+                    return (value, before / 2);
+                }
+                // the read was torn by a concurrent write, the value is dropped before retrying
+            }
+            backoff.backoff();
+        }
+    }
+
+    /// Replace the value, returning the old value and the sequence number of the new value
+    ///
+    /// Waits for a concurrent write to finish first, and like [`Rcu::replace`] for the old value to become reclaimable.
+    pub fn replace(&self, new_value: impl Into<Arc<T>>) -> (Arc<T>, u64) {
+        let new_value = new_value.into();
+
+        let mut backoff = AdaptiveBackoff::default();
+        let sequence = loop {
+            let sequence = self.sequence.load(Ordering::SeqCst);
+            if sequence % 2 == 0
+                && self
+                    .sequence
+                    .compare_exchange_weak(
+                        sequence,
+                        sequence + 1,
+                        Ordering::SeqCst,
+                        Ordering::SeqCst,
+                    )
+                    .is_ok()
+            {
+                break sequence;
+            }
+            backoff.backoff();
+        };
+
+        // Safety:
+        // - the old value is only returned once the epochs have been witnessed
+        let old_value = unsafe { self.arcu.swap_unreclaimed(new_value) };
+        self.sequence.store(sequence + 2, Ordering::SeqCst);
+
+        // readers are not held up while waiting, as the sequence is already even again
+        self.arcu.epoch_counter_pool().wait_for_epochs();
+        (old_value.into_inner(), sequence / 2 + 1)
+    }
+
+    /// The sequence number of the active value, or of the value being written
+    pub fn sequence(&self) -> u64 {
+        self.sequence.load(Ordering::SeqCst).div_ceil(2)
+    }
+
+    /// Read the value without its sequence number
+    ///
+    /// Unlike [`SeqArcu::raw_read`] this doesn't wait for a write in progress.
+    /// The underlying Arcu isn't exposed, as writing to it directly would bypass the sequence.
+    ///
+    /// ## Safety
+    /// - The epoch counter must not be used concurrently
+    /// - The epoch counter must belong to the EpochCounterPool of this Rcu
+    pub unsafe fn raw_read_value(&self, epoch_counter: &EpochCounter) -> Arc<T> {
+        // Safety: the caller upholds the safety requirements
+        unsafe { self.arcu.raw_read(epoch_counter) }
+    }
+
+    /// Consume the SeqArcu, returning the active value
+    pub fn into_inner(self) -> Arc<T> {
+        self.arcu.into_inner()
+    }
+}
+
+#[cfg(feature = "thread_local_counter")]
+impl<T> SeqArcu<T, crate::epoch_counters::GlobalEpochCounterPool> {
+    /// Read the value together with its sequence number, see [`SeqArcu::raw_read`]
+    pub fn read(&self) -> (crate::rcu_ref::RcuRef<T, T>, u64) {
+        let (value, sequence) =
+            crate::epoch_counters::with_thread_local_epoch_counter(|epoch_counter| {
+                // Safety:
+                // - we just registered the epoch counter
+                // - this is a thread local epoch counter that is only used here, so there can't be a concurrent use
+                unsafe { self.raw_read(epoch_counter) }
+            });
+        (crate::rcu_ref::RcuRef::new(value), sequence)
+    }
+
+    /// Read the value without its sequence number, see [`SeqArcu::raw_read_value`]
+    pub fn read_value(&self) -> crate::rcu_ref::RcuRef<T, T> {
+        self.arcu.read()
+    }
+}
@@ -0,0 +1,110 @@
+//! This module contains [`ShardedReadArcu`], an [`Arcu`] spreading the reference counting of readers over multiple shards
+
+use alloc::sync::Arc;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use crate::{
+    atomic::Arcu,
+    epoch_counters::{EpochCounterPool, GlobalEpochCounterPool},
+    rcu_ref::RcuRef,
+    reclaim::Unreclaimed,
+    Rcu,
+};
+
+/// A snapshot of the value of a [`ShardedReadArcu`], see [`ShardedReadArcu::read`]
+///
+/// This holds a strong count of the Arc in the reader's shard, not of the shared value itself.
+pub type ShardedRef<T> = RcuRef<Arc<T>, T>;
+
+/// An [`Arcu`] for values read by many threads at once, spreading the reads over multiple shards
+///
+/// Each read of an [`Arcu`] increments the strong count of the current value,
+/// so when many threads read the same value they all contend on the cache line of that count.
+/// Here each shard holds its own [`Arc`] pointing to the shared value,
+/// readers only increment the count of their thread's shard, so they only contend with the threads on the same shard.
+/// This trades an extra indirection per read for less contention, which hasn't been measured,
+/// so benchmark it against a plain [`Arcu`] on the intended hardware before relying on it.
+///
+/// Threads are assigned to shards round-robin on their first read.
+/// Writes are serialized and replace the value in each shard, then wait for the readers of all shards at once.
+/// They are still slower the more shards there are, so this is meant for rarely written values.
+pub struct ShardedReadArcu<T> {
+    shards: Box<[Arcu<Arc<T>, GlobalEpochCounterPool>]>,
+    writer: Mutex<()>,
+}
+
+static NEXT_SHARD: AtomicUsize = AtomicUsize::new(0);
+
+std::thread_local! {
+    static THREAD_SHARD: usize = NEXT_SHARD.fetch_add(1, Ordering::Relaxed);
+}
+
+impl<T> ShardedReadArcu<T> {
+    /// Create a new ShardedReadArcu with the given number of shards
+    ///
+    /// # Panics
+    /// - when `shards` is zero
+    pub fn new(initial: impl Into<Arc<T>>, shards: usize) -> Self {
+        assert!(shards > 0, "At least one shard is required!");
+        let initial = initial.into();
+        Self {
+            shards: (0..shards)
+                .map(|_| Arcu::new(Arc::clone(&initial), GlobalEpochCounterPool))
+                .collect(),
+            writer: Mutex::new(()),
+        }
+    }
+
+    /// The number of shards
+    pub fn shards(&self) -> usize {
+        self.shards.len()
+    }
+
+    /// Read the current value through the shard of the current thread, see [`Rcu::read`]
+    pub fn read(&self) -> ShardedRef<T> {
+        let shard = THREAD_SHARD.with(|shard| *shard) % self.shards.len();
+        RcuRef::map(self.shards[shard].read(), |value| &**value)
+    }
+
+    /// Replace the value in all shards, returning the old value
+    ///
+    /// Like [`Rcu::replace`] this blocks until the old value can be reclaimed in every shard.
+    /// While replacing, threads on different shards may read the old and the new value.
+    pub fn replace(&self, new_value: impl Into<Arc<T>>) -> Arc<T> {
+        let new_value = new_value.into();
+
+        let old_shard_values: Vec<Unreclaimed<Arc<Arc<T>>>> = {
+            // concurrent writers must not interleave, otherwise shards could end up with different values
+            let _writer = self
+                .writer
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner);
+
+            self.shards
+                .iter()
+                // Safety:
+                // - the old values are only dropped or handed out once the epochs have been witnessed below
+                .map(|shard| unsafe { shard.swap_unreclaimed(Arc::new(Arc::clone(&new_value))) })
+                .collect()
+        };
+
+        // all shards share the global pool, so a single wait covers the readers of every shard,
+        // the next writer doesn't need to wait for it
+        GlobalEpochCounterPool.wait_for_epochs();
+
+        let mut old_shard_values = old_shard_values.into_iter().map(Unreclaimed::into_inner);
+        let old_value = old_shard_values
+            .next()
+            .expect("there is at least one shard");
+        Arc::clone(&*old_value)
+    }
+}
+
+impl<T> core::fmt::Debug for ShardedReadArcu<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("ShardedReadArcu")
+            .field("shards", &self.shards.len())
+            .finish_non_exhaustive()
+    }
+}
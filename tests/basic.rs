@@ -178,3 +178,2404 @@ fn raw_update2<Arcu: Rcu<Item = usize, Pool = [Arc<EpochCounter>; 100]> + Send +
 
     drop(epoch_counters);
 }
+
+#[test]
+fn compare_exchange_arc() {
+    let epoch_counters: [_; 1] = std::array::from_fn(|_| Arc::new(EpochCounter::new()));
+    let rcu = arcu::atomic::Arcu::new(1, epoch_counters.clone());
+
+    let current = unsafe { rcu.raw_read(&epoch_counters[0]) };
+    let stale = Arc::new(1);
+
+    // the value is equal, but not the one in the rcu
+    let rejected = rcu.compare_exchange_arc(&stale, Arc::new(2), false);
+    assert!(rejected.is_err_and(|new| *new == 2));
+
+    let old = rcu.compare_exchange_arc(&current, Arc::new(3), false);
+    assert!(old.is_ok_and(|old| Arc::ptr_eq(&old, &current)));
+
+    let val = unsafe { rcu.raw_read(&epoch_counters[0]) };
+    assert_eq!(val.deref(), &3);
+}
+
+#[cfg(all(feature = "global_counters", feature = "thread_local_counter"))]
+#[test]
+fn caching_reader() {
+    use arcu::{cache::CachingReader, epoch_counters::GlobalEpochCounterPool};
+
+    let rcu = arcu::atomic::Arcu::new(1, GlobalEpochCounterPool);
+    let reader = CachingReader::new(0);
+
+    // ensure this thread's epoch counter is registered, so that read_fast can't fall back to the cache
+    assert_eq!(*rcu.read(), 1);
+    assert_eq!(*reader.read_fast(&rcu), 1);
+
+    rcu.replace(2);
+    assert_eq!(*reader.read_fast(&rcu), 2);
+}
+
+#[cfg(all(feature = "global_counters", feature = "thread_local_counter"))]
+#[test]
+fn make_mut() {
+    use arcu::epoch_counters::GlobalEpochCounterPool;
+
+    let rcu = arcu::atomic::Arcu::<Vec<_>, _>::new(vec![1, 2], GlobalEpochCounterPool);
+    let before = rcu.read();
+
+    {
+        let mut value = rcu.make_mut();
+        value.push(3);
+        // not committed until the guard is dropped
+        assert_eq!(*rcu.read(), [1, 2]);
+    }
+
+    assert_eq!(*rcu.read(), [1, 2, 3]);
+    assert_eq!(*before, [1, 2]);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn throttled_pool() {
+    use arcu::epoch_counters::ThrottledPool;
+
+    let epoch_counters: [_; 10] = std::array::from_fn(|_| Arc::new(EpochCounter::new()));
+    let rcu = arcu::atomic::Arcu::new(0, ThrottledPool::new(epoch_counters.clone(), 2));
+
+    std::thread::scope(|scope| {
+        for idx in 1..=100 {
+            let rcu = &rcu;
+            scope.spawn(move || rcu.replace(idx));
+        }
+    });
+
+    let val = unsafe { rcu.raw_read(&epoch_counters[0]) };
+    assert!((1..=100).contains(val.deref()));
+}
+
+#[cfg(all(feature = "global_counters", feature = "thread_local_counter"))]
+#[test]
+fn read_with_generation() {
+    use arcu::epoch_counters::GlobalEpochCounterPool;
+
+    let rcu = arcu::atomic::Arcu::new(0u64, GlobalEpochCounterPool);
+
+    let (value, generation) = rcu.read_with_generation();
+    assert_eq!((*value, generation), (0, 0));
+
+    std::thread::scope(|scope| {
+        let rcu = &rcu;
+        for _ in 0..4 {
+            scope.spawn(move || {
+                let mut last_generation = 0;
+                for _ in 0..1000 {
+                    // the single writer writes the generation as the value
+                    let (value, generation) = rcu.read_with_generation();
+                    assert_eq!(*value, generation);
+                    assert!(generation >= last_generation);
+                    last_generation = generation;
+                }
+            });
+        }
+        scope.spawn(move || {
+            for generation in 1..=1000 {
+                rcu.replace(generation);
+            }
+        });
+    });
+
+    let (value, generation) = rcu.read_with_generation();
+    assert_eq!((*value, generation), (1000, 1000));
+}
+
+#[cfg(feature = "thread_local_counter")]
+#[test]
+fn seq_arcu_consistent_pairs() {
+    use arcu::{epoch_counters::GlobalEpochCounterPool, seq::SeqArcu};
+    use std::collections::HashMap;
+
+    const WRITES: u32 = if cfg!(miri) { 20 } else { 500 };
+
+    let rcu = SeqArcu::new((0u32, 0u32), GlobalEpochCounterPool);
+    assert_eq!(rcu.sequence(), 0);
+
+    let (written, read) = std::thread::scope(|scope| {
+        let rcu = &rcu;
+        let readers: Vec<_> = (0..2)
+            .map(|_| {
+                scope.spawn(move || {
+                    (0..WRITES)
+                        .map(|_| {
+                            let (value, sequence) = rcu.read();
+                            (sequence, *value)
+                        })
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        let writers: Vec<_> = (1..=2)
+            .map(|writer| {
+                scope.spawn(move || {
+                    (0..WRITES)
+                        .map(|write| {
+                            let (_, sequence) = rcu.replace((writer, write));
+                            (sequence, (writer, write))
+                        })
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+
+        let written: HashMap<_, _> = writers
+            .into_iter()
+            .flat_map(|writer| writer.join().unwrap())
+            .chain([(0, (0, 0))])
+            .collect();
+        let read: Vec<_> = readers
+            .into_iter()
+            .flat_map(|reader| reader.join().unwrap())
+            .collect();
+        (written, read)
+    });
+
+    // the writers are serialized, so each sequence number has been written exactly once
+    assert_eq!(written.len() as u32, 2 * WRITES + 1);
+    assert_eq!(rcu.sequence(), u64::from(2 * WRITES));
+    // torn reads are retried, so each value is read with the sequence number it was written with
+    for (sequence, value) in read {
+        assert_eq!(written[&sequence], value);
+    }
+    let (value, sequence) = rcu.read();
+    assert_eq!(written[&sequence], *value);
+    assert_eq!(*rcu.read_value(), *value);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn deserialize_replace() {
+    use serde::de::{value::Error, IntoDeserializer};
+
+    let epoch_counters: [_; 1] = std::array::from_fn(|_| Arc::new(EpochCounter::new()));
+    let rcu = arcu::atomic::Arcu::new(1u32, epoch_counters.clone());
+
+    let old = rcu.deserialize_replace(IntoDeserializer::<Error>::into_deserializer(2u32));
+    assert_eq!(old.as_deref(), Ok(&1));
+
+    let invalid = rcu.deserialize_replace(IntoDeserializer::<Error>::into_deserializer("three"));
+    assert!(invalid.is_err());
+
+    let val = unsafe { rcu.raw_read(&epoch_counters[0]) };
+    assert_eq!(val.deref(), &2);
+}
+
+#[cfg(all(feature = "global_counters", feature = "thread_local_counter"))]
+#[test]
+fn with_read_borrowed() {
+    use arcu::epoch_counters::GlobalEpochCounterPool;
+
+    let rcu = arcu::atomic::Arcu::new(Arc::new(5), GlobalEpochCounterPool);
+
+    let doubled = rcu.with_read_borrowed(|value| value * 2);
+    assert_eq!(doubled, 10);
+
+    // the read critical section is left when the function panics
+    let result = std::panic::catch_unwind(|| rcu.with_read_borrowed(|_| panic!("read failed")));
+    assert!(result.is_err());
+    rcu.replace(6);
+    assert_eq!(*rcu.read(), 6);
+}
+
+#[test]
+fn read_session() {
+    let epoch_counters: [_; 2] = std::array::from_fn(|_| Arc::new(EpochCounter::new()));
+    let rcu = arcu::atomic::Arcu::new(1, epoch_counters.clone());
+
+    let arc = {
+        let session = unsafe { rcu.enter_read(&epoch_counters[0]) };
+        let first = session.load();
+        assert_eq!(first, &1);
+        assert_eq!(session.load_arc().deref(), first);
+        session.load_arc()
+    };
+
+    // the session has been left, so the replace doesn't block
+    rcu.replace(2);
+    assert_eq!(arc.deref(), &1);
+
+    let val = unsafe { rcu.raw_read(&epoch_counters[0]) };
+    assert_eq!(val.deref(), &2);
+}
+
+#[cfg(feature = "triomphe")]
+#[test]
+fn triomphe_arc() {
+    let epoch_counters: [_; 1] = std::array::from_fn(|_| Arc::new(EpochCounter::new()));
+    let rcu = arcu::atomic::Arcu::from_arc(triomphe::Arc::new(1), epoch_counters.clone());
+
+    let current = unsafe { rcu.raw_read_arc(&epoch_counters[0]) };
+    assert_eq!(*current, 1);
+
+    let old = rcu.replace_arc(triomphe::Arc::new(2));
+    assert!(triomphe::Arc::ptr_eq(&old, &current));
+    drop((old, current));
+
+    let old = rcu.compare_exchange_arc(&triomphe::Arc::new(2), triomphe::Arc::new(3), false);
+    assert!(old.is_err());
+
+    let current = unsafe { rcu.raw_read_arc(&epoch_counters[0]) };
+    assert_eq!(*current, 2);
+    assert_eq!(triomphe::Arc::count(&current), 2);
+}
+
+#[cfg(feature = "thread_local_counter")]
+#[test]
+fn read_map_snapshot() {
+    use arcu::epoch_counters::GlobalEpochCounterPool;
+
+    let shards = std::collections::HashMap::from([
+        ("a", arcu::atomic::Arcu::new(1, GlobalEpochCounterPool)),
+        ("b", arcu::atomic::Arcu::new(2, GlobalEpochCounterPool)),
+    ]);
+
+    let snapshot =
+        arcu::atomic::read_map_snapshot(shards.iter().map(|(name, shard)| (*name, shard)));
+    shards["a"].replace(3);
+
+    assert_eq!(snapshot.len(), 2);
+    assert_eq!(*snapshot["a"], 1);
+    assert_eq!(*snapshot["b"], 2);
+    assert_eq!(*shards["a"].read(), 3);
+}
+
+#[cfg(feature = "thread_local_counter")]
+#[test]
+fn try_replace_exclusive() {
+    use arcu::epoch_counters::GlobalEpochCounterPool;
+
+    let expected = Arc::new(0);
+    let rcu = arcu::atomic::Arcu::new(expected.clone(), GlobalEpochCounterPool);
+    let barrier = std::sync::Barrier::new(2);
+
+    let results: Vec<_> = std::thread::scope(|s| {
+        let writers: Vec<_> = (1..=2)
+            .map(|value| {
+                let (rcu, barrier, expected) = (&rcu, &barrier, &expected);
+                s.spawn(move || {
+                    barrier.wait();
+                    rcu.try_replace_exclusive(expected, value)
+                        .map(|old| *old)
+                        .map_err(|conflict| (*conflict.current, *conflict.rejected))
+                })
+            })
+            .collect();
+        writers.into_iter().map(|w| w.join().unwrap()).collect()
+    });
+
+    let winner = *rcu.read();
+    assert_eq!(results.iter().filter(|r| r.is_ok()).count(), 1);
+    for result in results {
+        match result {
+            Ok(old) => assert_eq!(old, 0),
+            Err((current, rejected)) => {
+                assert_eq!(current, winner);
+                assert_ne!(rejected, winner);
+            }
+        }
+    }
+}
+
+#[test]
+fn try_update_panic() {
+    fn check<R: Rcu<Item = u32, Pool = [Arc<EpochCounter>; 1]>>() {
+        let epoch_counters: [_; 1] = [Arc::new(EpochCounter::new())];
+        let initial = Arc::new(1);
+        let rcu = R::new(initial.clone(), epoch_counters.clone());
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| unsafe {
+            rcu.raw_try_update(
+                |_| -> Option<Arc<u32>> { panic!("update failed") },
+                &epoch_counters[0],
+            )
+        }));
+        assert!(result.is_err());
+
+        // the read before the panic left the critical section and didn't leak the old value
+        assert_eq!(Arc::strong_count(&initial), 2);
+        assert_eq!(*unsafe { rcu.raw_read(&epoch_counters[0]) }, 1);
+
+        let old = rcu.replace(2);
+        assert!(Arc::ptr_eq(&old, &initial));
+        drop(rcu);
+        assert_eq!(Arc::strong_count(&initial), 2);
+    }
+
+    check::<arcu::atomic::Arcu<u32, _>>();
+    check::<arcu::rwlock::Arcu<u32, _>>();
+}
+
+#[cfg(feature = "thread_local_counter")]
+#[test]
+fn cached_reader() {
+    use arcu::{cache::CachedReader, epoch_counters::GlobalEpochCounterPool};
+
+    let rcu = arcu::atomic::Arcu::new(1, GlobalEpochCounterPool);
+    let mut reader = CachedReader::new();
+
+    let first = Arc::clone(reader.read(&rcu));
+    assert_eq!(*first, 1);
+    assert!(Arc::ptr_eq(reader.read(&rcu), &first));
+
+    rcu.replace(2);
+    assert_eq!(**reader.read(&rcu), 2);
+    assert!(!Arc::ptr_eq(reader.read(&rcu), &first));
+
+    reader.clear();
+    assert_eq!(Arc::strong_count(&first), 1);
+}
+
+#[test]
+fn generation_wraps() {
+    use arcu::atomic::generation_is_newer;
+
+    let epoch_counters: [_; 1] = [Arc::new(EpochCounter::new())];
+    let rcu = arcu::atomic::Arcu::from_arc_with_generation(
+        Arc::new(0),
+        u64::MAX - 1,
+        epoch_counters.clone(),
+    );
+
+    let mut last = unsafe { rcu.raw_read_with_generation(&epoch_counters[0]) }.1;
+    for value in 1..4 {
+        rcu.replace(value);
+        let (current, generation) = unsafe { rcu.raw_read_with_generation(&epoch_counters[0]) };
+        assert_eq!(*current, value);
+        assert!(generation_is_newer(generation, last));
+        assert!(!generation_is_newer(last, generation));
+        last = generation;
+    }
+    assert_eq!(last, 1);
+    assert!(!generation_is_newer(last, last));
+}
+
+#[test]
+fn generation_counts_changes() {
+    let epoch_counters: [_; 1] = [Arc::new(EpochCounter::new())];
+    let rcu = arcu::atomic::Arcu::new(1, epoch_counters.clone());
+    assert_eq!(rcu.generation(), 0);
+
+    rcu.replace(2);
+    assert_eq!(rcu.generation(), 1);
+
+    // Safety: the epoch counter belongs to the pool and is only used here
+    unsafe { rcu.raw_try_update(|old| Some(Arc::new(old + 1)), &epoch_counters[0]) };
+    assert_eq!(rcu.generation(), 2);
+
+    // writes that don't change the value don't advance the generation
+    unsafe { rcu.raw_try_update(|_| None, &epoch_counters[0]) };
+    let stale = Arc::new(3);
+    assert!(rcu
+        .compare_exchange_arc(&stale, Arc::new(4), false)
+        .is_err());
+    assert_eq!(rcu.generation(), 2);
+    assert_eq!(*unsafe { rcu.raw_read(&epoch_counters[0]) }, 3);
+}
+
+#[test]
+fn into_arc() {
+    fn check<R: Rcu<Item = u32, Pool = [Arc<EpochCounter>; 1]>>()
+    where
+        Arc<u32>: From<R>,
+    {
+        let epoch_counters: [_; 1] = [Arc::new(EpochCounter::new())];
+        let rcu = R::new(1, epoch_counters.clone());
+        rcu.replace(2);
+
+        let arc: Arc<u32> = rcu.into();
+        assert_eq!(*arc, 2);
+        assert_eq!(Arc::strong_count(&arc), 1);
+        assert_eq!(Arc::strong_count(&epoch_counters[0]), 1);
+    }
+
+    check::<arcu::atomic::Arcu<u32, _>>();
+    check::<arcu::rwlock::Arcu<u32, _>>();
+}
+
+#[test]
+fn into_inner() {
+    let epoch_counters: [_; 1] = [Arc::new(EpochCounter::new())];
+    let value = Arc::new(String::from("value"));
+
+    let rcu = arcu::atomic::Arcu::new(value.clone(), epoch_counters.clone());
+    assert_eq!(Arc::strong_count(&value), 2);
+    let inner = rcu.into_inner();
+    assert!(Arc::ptr_eq(&inner, &value));
+    assert_eq!(Arc::strong_count(&value), 2);
+    drop(inner);
+    assert_eq!(Arc::strong_count(&value), 1);
+
+    let rcu = arcu::rwlock::Arcu::new(value.clone(), epoch_counters.clone());
+    let inner = rcu.into_inner();
+    assert!(Arc::ptr_eq(&inner, &value));
+    drop(inner);
+    assert_eq!(Arc::strong_count(&value), 1);
+    assert_eq!(Arc::strong_count(&epoch_counters[0]), 1);
+
+    let rcu = arcu::atomic::Arcu::<[u32], _>::new(Arc::from([1, 2]), epoch_counters);
+    assert_eq!(*rcu.into_inner(), [1, 2]);
+}
+
+#[test]
+fn get_mut() {
+    fn check<R: Rcu<Item = Vec<u32>, Pool = [Arc<EpochCounter>; 1]>>(
+        get_mut: fn(&mut R) -> Option<&mut Vec<u32>>,
+    ) {
+        let epoch_counter = Arc::new(EpochCounter::new());
+        let mut rcu = R::new(vec![1], [epoch_counter.clone()]);
+
+        get_mut(&mut rcu).unwrap().push(2);
+        assert_eq!(*unsafe { rcu.raw_read(&epoch_counter) }, [1, 2]);
+
+        // a snapshot shares the value
+        let snapshot = unsafe { rcu.raw_read(&epoch_counter) };
+        assert!(get_mut(&mut rcu).is_none());
+        drop(snapshot);
+        assert!(get_mut(&mut rcu).is_some());
+
+        // the replaced value is no longer shared with the Rcu
+        let old = rcu.replace(vec![3]);
+        get_mut(&mut rcu).unwrap().push(4);
+        assert_eq!(*old, [1, 2]);
+        assert_eq!(*unsafe { rcu.raw_read(&epoch_counter) }, [3, 4]);
+    }
+
+    check::<arcu::atomic::Arcu<_, _>>(arcu::atomic::Arcu::get_mut);
+    check::<arcu::rwlock::Arcu<_, _>>(arcu::rwlock::Arcu::get_mut);
+
+    let epoch_counters = [Arc::new(EpochCounter::new())];
+    let mut rcu = arcu::atomic::Arcu::<[u32], _>::new(Arc::from([1, 2]), epoch_counters);
+    rcu.get_mut().unwrap()[0] = 3;
+    assert_eq!(*rcu.into_inner(), [3, 2]);
+}
+
+#[cfg(feature = "thread_local_counter")]
+#[test]
+fn replace_validated() {
+    use arcu::epoch_counters::GlobalEpochCounterPool;
+
+    let rcu = arcu::atomic::Arcu::new(1, GlobalEpochCounterPool);
+    let only_increase = |current: &u32, new: &u32| {
+        if new > current {
+            Ok(())
+        } else {
+            Err(*current)
+        }
+    };
+
+    let old = rcu.replace_validated(3, only_increase).unwrap();
+    assert_eq!(*old, 1);
+
+    let (err, rejected) = rcu.replace_validated(2, only_increase).unwrap_err();
+    assert_eq!(err, 3);
+    assert_eq!(*rejected, 2);
+    assert_eq!(*rcu.read(), 3);
+}
+
+#[cfg(feature = "thread_local_counter")]
+#[test]
+fn iter_snapshot() {
+    use arcu::epoch_counters::GlobalEpochCounterPool;
+
+    let rcu = arcu::atomic::Arcu::<Vec<_>, _>::new(vec![1, 2, 3], GlobalEpochCounterPool);
+
+    let mut sum = 0;
+    for element in rcu.iter_snapshot() {
+        // replacing doesn't affect the running iteration
+        rcu.replace(vec![]);
+        sum += *element;
+    }
+    assert_eq!(sum, 6);
+    assert_eq!(rcu.iter_snapshot().count(), 0);
+}
+
+#[cfg(feature = "thread_local_counter")]
+#[test]
+fn value_eq() {
+    use arcu::epoch_counters::GlobalEpochCounterPool;
+
+    let rcu = arcu::atomic::Arcu::new(1, GlobalEpochCounterPool);
+    assert_eq!(rcu, 1);
+    assert!(rcu.value_eq(&1));
+
+    rcu.replace(2);
+    assert_ne!(rcu, 1);
+    assert!(!rcu.value_eq(&1));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn recycling_arcu() {
+    use arcu::recycling::RecyclingArcu;
+
+    let epoch_counters: [_; 1] = [Arc::new(EpochCounter::new())];
+    let rcu = RecyclingArcu::new(1, epoch_counters.clone(), 1);
+    let read = || unsafe { rcu.arcu().raw_read(&epoch_counters[0]) };
+
+    let initial = Arc::as_ptr(&read());
+    rcu.replace_recycling(2);
+    assert_eq!(rcu.recycled(), 1);
+
+    // the allocation of the initial value is reused
+    rcu.replace_recycling(3);
+    let current = read();
+    assert_eq!(*current, 3);
+    assert_eq!(Arc::as_ptr(&current), initial);
+
+    // the replaced value is still referenced and can't be recycled
+    rcu.replace_recycling(4);
+    assert_eq!(rcu.recycled(), 0);
+    assert_eq!(*current, 3);
+}
+
+#[cfg(feature = "thread_local_counter")]
+#[test]
+fn update_if_changed() {
+    use arcu::epoch_counters::GlobalEpochCounterPool;
+
+    let rcu = arcu::atomic::Arcu::new(1, GlobalEpochCounterPool);
+
+    assert!(rcu.update_if_changed(|old| Arc::new(*old)).is_none());
+    let old = rcu.update_if_changed(|old| Arc::new(old + 1));
+    assert_eq!(old.as_deref(), Some(&1));
+    assert_eq!(*rcu.read(), 2);
+}
+
+#[test]
+fn epoch_counter_cache_line() {
+    let counters = [EpochCounter::new(), EpochCounter::new()];
+    let distance = (&counters[1] as *const EpochCounter as usize)
+        - (&counters[0] as *const EpochCounter as usize);
+    assert!(distance >= 64);
+}
+
+#[cfg(feature = "crossbeam-epoch")]
+#[test]
+fn crossbeam_epoch_pool() {
+    use arcu::epoch_counters::CrossbeamEpochPool;
+
+    let rcu = arcu::atomic::Arcu::new(0, CrossbeamEpochPool);
+
+    std::thread::scope(|s| {
+        for _ in 0..4 {
+            s.spawn(|| {
+                let mut last = 0;
+                while last < 100 {
+                    let guard = crossbeam_epoch::pin();
+                    let current = rcu.read_pinned(&guard);
+                    drop(guard);
+                    assert!(*current >= last);
+                    last = *current;
+                }
+            });
+        }
+
+        for value in 1..=100 {
+            rcu.replace(value);
+        }
+    });
+
+    let old = rcu.replace(101);
+    assert_eq!(Arc::strong_count(&old), 1);
+
+    // the pool can't tell which epoch counters belong to it, so the debug assertion of raw reads passes
+    let epoch_counter = EpochCounter::new();
+    let guard = crossbeam_epoch::pin();
+    assert_eq!(*unsafe { rcu.raw_read(&epoch_counter) }, 101);
+    drop(guard);
+}
+
+#[cfg(feature = "thread_local_counter")]
+#[test]
+fn reader_cache() {
+    use arcu::{cache::ReaderCache, epoch_counters::GlobalEpochCounterPool, rcu_ref::RcuRef};
+
+    let rcu = arcu::atomic::Arcu::new(1, GlobalEpochCounterPool);
+    let mut cache = ReaderCache::new();
+
+    let first = cache.read_cached(&rcu);
+    assert_eq!(*first, 1);
+    assert!(RcuRef::ptr_eq(&cache.read_cached(&rcu), &first));
+
+    rcu.replace(2);
+    let second = cache.read_cached(&rcu);
+    assert_eq!(*second, 2);
+    assert!(!RcuRef::same_epoch(&second, &first));
+
+    // a cache used with different Arcus at the same generation returns the value of each
+    let a = arcu::atomic::Arcu::new(10, GlobalEpochCounterPool);
+    let b = arcu::atomic::Arcu::new(20, GlobalEpochCounterPool);
+    let mut cache = ReaderCache::new();
+    assert_eq!(a.generation(), b.generation());
+    assert_eq!(*cache.read_cached(&a), 10);
+    assert_eq!(*cache.read_cached(&b), 20);
+    assert_eq!(*cache.read_cached(&a), 10);
+}
+
+#[cfg(feature = "thread_local_counter")]
+#[test]
+fn arcu_read_macro() {
+    use arcu::epoch_counters::GlobalEpochCounterPool;
+
+    let a = arcu::atomic::Arcu::new(1, GlobalEpochCounterPool);
+    let b = arcu::atomic::Arcu::new(2, GlobalEpochCounterPool);
+
+    let sum = arcu::arcu_read!(let a = &a; let b = &b; { a + b });
+    assert_eq!(sum, 3);
+
+    // the read critical section is left on early return
+    fn first_positive(
+        a: &arcu::atomic::Arcu<i32, GlobalEpochCounterPool>,
+        b: &arcu::atomic::Arcu<i32, GlobalEpochCounterPool>,
+    ) -> i32 {
+        arcu::arcu_read!(let a = a; let b = b; {
+            if *a > 0 {
+                return *a;
+            }
+            *b
+        })
+    }
+    assert_eq!(first_positive(&a, &b), 1);
+    a.replace(-1);
+    assert_eq!(first_positive(&a, &b), 2);
+
+    // and on panic
+    let result = std::panic::catch_unwind(|| arcu::arcu_read!(let a = &a; { panic!("{a}") }));
+    assert!(result.is_err());
+    b.replace(3);
+    assert_eq!(*b.read(), 3);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn leaky_arcu() {
+    use arcu::leaky::LeakyArcu;
+
+    let mut rcu = LeakyArcu::new(1);
+
+    std::thread::scope(|s| {
+        let rcu = &rcu;
+        let first = rcu.read();
+        s.spawn(move || rcu.replace(2)).join().unwrap();
+
+        // the replaced value is kept alive for existing references
+        assert_eq!(*first, 1);
+        assert_eq!(*rcu.read(), 2);
+        assert_eq!(rcu.retired(), 1);
+    });
+
+    rcu.gc();
+    assert_eq!(rcu.retired(), 0);
+    *rcu.get_mut() += 1;
+    assert_eq!(*rcu.read(), 3);
+}
+
+#[test]
+fn zero_sized_atomic() {
+    zero_sized::<arcu::atomic::Arcu<(), _>, _>(());
+    zero_sized::<arcu::atomic::Arcu<[u8; 0], _>, _>([]);
+}
+
+#[test]
+fn zero_sized_rwlock() {
+    zero_sized::<arcu::rwlock::Arcu<(), _>, _>(());
+    zero_sized::<arcu::rwlock::Arcu<[u8; 0], _>, _>([]);
+}
+
+fn zero_sized<R: Rcu<Item = T, Pool = [Arc<EpochCounter>; 1]>, T: Clone>(value: T) {
+    let epoch_counters: [_; 1] = [Arc::new(EpochCounter::new())];
+    let initial = Arc::new(value.clone());
+    let rcu = R::new(initial.clone(), epoch_counters.clone());
+
+    let read = unsafe { rcu.raw_read(&epoch_counters[0]) };
+    assert!(Arc::ptr_eq(&read, &initial));
+
+    // every Arc has its own allocation for the reference counts, so they can be told apart
+    let replacement = Arc::new(value.clone());
+    assert!(!Arc::ptr_eq(&replacement, &initial));
+    let old = rcu.replace(replacement.clone());
+    assert!(Arc::ptr_eq(&old, &initial));
+
+    // an update based on a stale read is retried, weak compare exchanges may cause additional retries
+    let mut calls = 0;
+    let old = unsafe {
+        rcu.raw_try_update(
+            |_| {
+                calls += 1;
+                if calls == 1 {
+                    rcu.replace(value.clone());
+                }
+                Some(Arc::new(value.clone()))
+            },
+            &epoch_counters[0],
+        )
+    };
+    assert!(calls >= 2);
+    assert!(!Arc::ptr_eq(&old.unwrap(), &replacement));
+}
+
+#[cfg(feature = "thread_local_counter")]
+#[test]
+fn local_epoch_counter_pool() {
+    use arcu::epoch_counters::LocalEpochCounterPool;
+
+    let pool_a = LocalEpochCounterPool::new();
+    let pool_b = LocalEpochCounterPool::new();
+    let rcu_a = arcu::atomic::Arcu::new(1, pool_a.clone());
+    let rcu_b = arcu::atomic::Arcu::new(10, pool_b.clone());
+
+    // a reader of one pool doesn't block writers of the other
+    let epoch_counter = Arc::new(EpochCounter::new());
+    pool_a.register(Arc::downgrade(&epoch_counter));
+    let session = unsafe { rcu_a.enter_read(&epoch_counter) };
+    assert_eq!(*session.load(), 1);
+    assert_eq!(*rcu_b.replace(11), 10);
+    drop(session);
+    drop(epoch_counter);
+    assert_eq!(pool_a.prune(), 1);
+
+    // the thread local epoch counter is registered with each pool on the first read
+    std::thread::scope(|scope| {
+        let readers: Vec<_> = (0..4)
+            .map(|_| {
+                scope.spawn(|| {
+                    for _ in 0..100 {
+                        rcu_a.try_update(|old| Some(old + 1));
+                        assert!(*rcu_b.read() >= 11);
+                    }
+                })
+            })
+            .collect();
+        rcu_b.replace(12);
+        // joining explicitly waits for the thread locals to be dropped
+        for reader in readers {
+            reader.join().unwrap();
+        }
+    });
+    assert_eq!(*rcu_a.read(), 401);
+    assert_eq!(*rcu_b.read(), 12);
+
+    // the exited threads' epoch counters are dropped
+    assert_eq!(pool_a.prune(), 4);
+    assert_eq!(pool_b.prune(), 4);
+
+    // registering with a new pool while using the epoch counter of another one, dropping the entry of a dropped pool
+    drop(rcu_b);
+    drop(pool_b);
+    let rcu_c = arcu::atomic::Arcu::new(100, LocalEpochCounterPool::new());
+    rcu_a.try_update(|old| Some(old + *rcu_c.read()));
+    assert_eq!(*rcu_a.read(), 501);
+}
+
+#[test]
+fn try_reclaim() {
+    let epoch_counters: [_; 2] = std::array::from_fn(|_| Arc::new(EpochCounter::new()));
+    let rcu = arcu::atomic::Arcu::new(1, epoch_counters.clone());
+
+    // no reader, reclaimable right away
+    let old = rcu.replace_reclaimed(2).try_reclaim().ok().unwrap();
+    assert_eq!(*old, 1);
+
+    let session = unsafe { rcu.enter_read(&epoch_counters[0]) };
+    let current = session.load();
+    let reclaim = rcu.replace_reclaimed(3);
+
+    // the new value is visible immediately, the old one is still in use
+    assert_eq!(*unsafe { rcu.raw_read(&epoch_counters[1]) }, 3);
+    let reclaim = reclaim.try_reclaim().err().unwrap();
+    assert_eq!(*current, 2);
+
+    drop(session);
+    assert_eq!(*reclaim.reclaim(), 2);
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn replace_reclaimed() {
+    use core::future::Future;
+    use core::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+
+    fn noop_raw_waker() -> RawWaker {
+        fn clone(_: *const ()) -> RawWaker {
+            noop_raw_waker()
+        }
+        fn noop(_: *const ()) {}
+        static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+        RawWaker::new(core::ptr::null(), &VTABLE)
+    }
+    let waker = unsafe { Waker::from_raw(noop_raw_waker()) };
+    let mut cx = Context::from_waker(&waker);
+
+    let epoch_counters: [_; 2] = std::array::from_fn(|_| Arc::new(EpochCounter::new()));
+    let rcu = arcu::atomic::Arcu::new(1, epoch_counters.clone());
+
+    let session = unsafe { rcu.enter_read(&epoch_counters[0]) };
+    let old = session.load();
+    let mut reclaim = Box::pin(rcu.replace_reclaimed(2));
+
+    // the new value is visible immediately, the old one is still in use
+    assert_eq!(*unsafe { rcu.raw_read(&epoch_counters[1]) }, 2);
+    assert_eq!(*old, 1);
+    assert!(reclaim.as_mut().poll(&mut cx).is_pending());
+
+    drop(session);
+    match reclaim.as_mut().poll(&mut cx) {
+        Poll::Ready(old) => assert_eq!(*old, 1),
+        Poll::Pending => panic!("the old value should be reclaimable"),
+    }
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn replace_async_computed() {
+    use core::future::Future;
+    use core::pin::Pin;
+    use core::task::{Context, Poll};
+    use std::task::Wake;
+
+    struct NoopWaker;
+
+    impl Wake for NoopWaker {
+        fn wake(self: Arc<Self>) {}
+    }
+
+    /// pending on the first poll, like a fetch that hasn't finished yet
+    struct Fetch(Option<u32>, bool);
+
+    impl Future for Fetch {
+        type Output = u32;
+
+        fn poll(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<u32> {
+            if core::mem::replace(&mut self.1, true) {
+                Poll::Ready(self.0.take().unwrap())
+            } else {
+                Poll::Pending
+            }
+        }
+    }
+
+    let waker = Arc::new(NoopWaker).into();
+    let mut cx = Context::from_waker(&waker);
+
+    let epoch_counters: [_; 1] = [Arc::new(EpochCounter::new())];
+    let rcu = arcu::atomic::Arcu::new(1, epoch_counters.clone());
+
+    let mut replace = Box::pin(rcu.replace_async_computed(Fetch(Some(2), false)));
+    assert!(replace.as_mut().poll(&mut cx).is_pending());
+    // reads continue with the old value while computing
+    assert_eq!(*unsafe { rcu.raw_read(&epoch_counters[0]) }, 1);
+
+    match replace.as_mut().poll(&mut cx) {
+        Poll::Ready(old) => assert_eq!(*old, 1),
+        Poll::Pending => panic!("the old value should be reclaimable"),
+    }
+    assert_eq!(*unsafe { rcu.raw_read(&epoch_counters[0]) }, 2);
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn replace_async() {
+    use arcu::epoch_counters::WaitForEpochs;
+    use core::future::Future;
+    use core::sync::atomic::{AtomicUsize, Ordering};
+    use core::task::{Context, Poll};
+    use std::task::Wake;
+
+    struct CountingWaker(AtomicUsize);
+
+    impl Wake for CountingWaker {
+        fn wake(self: Arc<Self>) {
+            self.wake_by_ref();
+        }
+
+        fn wake_by_ref(self: &Arc<Self>) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    let wakes = Arc::new(CountingWaker(AtomicUsize::new(0)));
+    let waker = Arc::clone(&wakes).into();
+    let mut cx = Context::from_waker(&waker);
+
+    let epoch_counters: [_; 2] = std::array::from_fn(|_| Arc::new(EpochCounter::new()));
+    let rcu = arcu::atomic::Arcu::new(1, epoch_counters.clone());
+
+    let session = unsafe { rcu.enter_read(&epoch_counters[0]) };
+    let mut replace = Box::pin(rcu.replace_async(2));
+
+    // instead of blocking the executor the future yields, asking to be polled again
+    assert!(replace.as_mut().poll(&mut cx).is_pending());
+    assert_eq!(*unsafe { rcu.raw_read(&epoch_counters[1]) }, 2);
+    assert_eq!(wakes.0.load(Ordering::Relaxed), 1);
+
+    // the wait on its own, e.g. for other uses of the pool
+    let mut wait = Box::pin(WaitForEpochs::new(&epoch_counters));
+    assert!(wait.as_mut().poll(&mut cx).is_pending());
+
+    drop(session);
+    match replace.as_mut().poll(&mut cx) {
+        Poll::Ready(old) => assert_eq!(*old, 1),
+        Poll::Pending => panic!("the old value should be reclaimable"),
+    }
+    assert!(wait.as_mut().poll(&mut cx).is_ready());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn branded() {
+    use arcu::branded::with_brand;
+
+    fn check<'id, R: Rcu<Item = u32, Pool = arcu::branded::BrandedPool<'id>> + Sync>(
+        pool: &arcu::branded::BrandedPool<'id>,
+    ) {
+        let rcu = R::new(0, pool.clone());
+
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                let counter = pool.counter();
+                let rcu = &rcu;
+                scope.spawn(move || {
+                    for _ in 0..100 {
+                        counter.try_update(rcu, |old| Some(old + 1));
+                    }
+                    counter.with_read(rcu, |value| assert!(*value >= 100));
+                });
+            }
+        });
+
+        assert_eq!(*pool.counter().read(&rcu), 400);
+    }
+
+    with_brand(|pool| {
+        check::<arcu::atomic::Arcu<u32, _>>(&pool);
+        check::<arcu::rwlock::Arcu<u32, _>>(&pool);
+    });
+}
+
+#[cfg(debug_assertions)]
+#[test]
+#[should_panic = "The epoch counter does not belong to the EpochCounterPool of this Arcu!"]
+fn foreign_epoch_counter() {
+    let epoch_counters: [_; 1] = [Arc::new(EpochCounter::new())];
+    let rcu = arcu::atomic::Arcu::new(1, epoch_counters);
+
+    let foreign = EpochCounter::new();
+    let _ = unsafe { rcu.raw_read(&foreign) };
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn deferred_reclaimer() {
+    use arcu::deferred::DeferredReclaimer;
+
+    let epoch_counters: [_; 2] = std::array::from_fn(|_| Arc::new(EpochCounter::new()));
+    let rcu = DeferredReclaimer::new(0, epoch_counters.clone(), Some(2));
+
+    let session = unsafe { rcu.arcu().enter_read(&epoch_counters[0]) };
+    let old = session.load();
+
+    // writes don't wait for the reader
+    assert!(rcu.replace_deferred(1).is_ok());
+    assert!(rcu.replace_deferred(2).is_ok());
+    assert_eq!(rcu.pending_reclamations(), 2);
+    assert_eq!(*unsafe { rcu.arcu().raw_read(&epoch_counters[1]) }, 2);
+
+    // until the limit of pending reclamations is reached
+    assert!(rcu
+        .replace_deferred(3)
+        .is_err_and(|rejected| *rejected == 3));
+    assert_eq!(rcu.reclaim(), 0);
+    assert_eq!(*old, 0);
+
+    drop(session);
+    assert_eq!(rcu.reclaim(), 2);
+    assert_eq!(rcu.pending_reclamations(), 0);
+    assert!(rcu.replace_deferred(3).is_ok());
+}
+
+#[cfg(feature = "thread_local_counter")]
+#[test]
+fn active_counts() {
+    use arcu::epoch_counters::GlobalEpochCounterPool;
+
+    let value = Arc::new(1);
+    let weak = Arc::downgrade(&value);
+    let rcu = arcu::atomic::Arcu::new(value, GlobalEpochCounterPool);
+    assert_eq!(rcu.active_counts(), (0, 1));
+
+    let snapshot = rcu.read();
+    assert_eq!(rcu.active_counts(), (1, 1));
+    drop(snapshot);
+    assert_eq!(rcu.active_counts(), (0, 1));
+
+    rcu.replace(2);
+    assert_eq!(rcu.active_counts(), (0, 0));
+    assert!(weak.upgrade().is_none());
+}
+
+#[test]
+fn strong_updates() {
+    let epoch_counters: [_; 1] = [Arc::new(EpochCounter::new())];
+    let rcu = arcu::atomic::Arcu::new(0, epoch_counters.clone()).with_weak_updates(false);
+
+    // without concurrent writes the update function runs exactly once
+    for expected in 1..=100 {
+        let mut calls = 0;
+        let old = unsafe {
+            rcu.raw_try_update(
+                |old| {
+                    calls += 1;
+                    Some(Arc::new(old + 1))
+                },
+                &epoch_counters[0],
+            )
+        };
+        assert_eq!(calls, 1);
+        assert_eq!(old.as_deref(), Some(&(expected - 1)));
+    }
+}
+
+#[test]
+fn replace_tracked() {
+    fn check<R: Rcu<Item = u32, Pool = [Arc<EpochCounter>; 1]>>() {
+        let epoch_counters: [_; 1] = [Arc::new(EpochCounter::new())];
+        let rcu = R::new(1, epoch_counters.clone());
+
+        let (old, tracker) = rcu.replace_tracked(2);
+        assert_eq!(*old, 1);
+        assert_eq!(tracker.upgrade().as_deref(), Some(&2));
+
+        let snapshot = unsafe { rcu.raw_read(&epoch_counters[0]) };
+        drop(rcu.replace(3));
+        // the reader still holds the tracked value
+        assert!(tracker.upgrade().is_some());
+
+        drop(snapshot);
+        assert!(tracker.upgrade().is_none());
+    }
+
+    check::<arcu::atomic::Arcu<u32, _>>();
+    check::<arcu::rwlock::Arcu<u32, _>>();
+}
+
+#[cfg(feature = "thread_local_counter")]
+#[test]
+fn fold_replace() {
+    use arcu::epoch_counters::GlobalEpochCounterPool;
+
+    let rcu = arcu::atomic::Arcu::new(vec![0u32], GlobalEpochCounterPool);
+    let initial = rcu.read();
+
+    let replaced = rcu.fold_replace([1, 2, 3], |current: &Vec<u32>, delta| {
+        let mut next = current.clone();
+        next.push(current.last().unwrap() + delta);
+        next
+    });
+
+    assert_eq!(*rcu.read(), [0, 1, 3, 6]);
+    assert_eq!(replaced.len(), 3);
+    assert!(core::ptr::eq(Arc::as_ptr(&replaced[0]), &*initial));
+    assert_eq!(*replaced[2], [0, 1, 3]);
+
+    // concurrent writers are retried per step, no delta is lost
+    let rcu = arcu::atomic::Arcu::new(0u32, GlobalEpochCounterPool);
+    std::thread::scope(|scope| {
+        for _ in 0..4 {
+            scope.spawn(|| {
+                rcu.fold_replace(0..100, |current, _| current + 1);
+            });
+        }
+    });
+    assert_eq!(*rcu.read(), 400);
+}
+
+#[test]
+fn tight_read_loop_during_replaces() {
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    const WRITES: u32 = if cfg!(miri) { 20 } else { 2000 };
+
+    fn check<R: Rcu<Item = Vec<u32>, Pool = [Arc<EpochCounter>; 1]> + Sync>() {
+        let epoch_counters: [_; 1] = [Arc::new(EpochCounter::new())];
+        let rcu = R::new(vec![0; 16], epoch_counters.clone());
+        let started = AtomicBool::new(false);
+        let done = AtomicBool::new(false);
+
+        std::thread::scope(|scope| {
+            scope.spawn(|| {
+                // many more reads than the writer's wait can observe, so the epoch moves on a lot in between
+                while !done.load(Ordering::Relaxed) {
+                    let value = unsafe { rcu.raw_read(&epoch_counters[0]) };
+                    assert!(value.iter().all(|elem| *elem == value[0]));
+                    started.store(true, Ordering::Relaxed);
+                }
+            });
+
+            while !started.load(Ordering::Relaxed) {
+                std::thread::yield_now();
+            }
+            for generation in 1..=WRITES {
+                rcu.replace(vec![generation; 16]);
+            }
+            done.store(true, Ordering::Relaxed);
+        });
+    }
+
+    check::<arcu::atomic::Arcu<_, _>>();
+    check::<arcu::rwlock::Arcu<_, _>>();
+}
+
+#[test]
+fn replace_with_drop_sink() {
+    fn check<R: Rcu<Item = u32, Pool = [Arc<EpochCounter>; 1]>>() {
+        let epoch_counters: [_; 1] = [Arc::new(EpochCounter::new())];
+        let rcu = R::new(1, epoch_counters.clone());
+        let old = unsafe { rcu.raw_read(&epoch_counters[0]) };
+        let (sender, receiver) = std::sync::mpsc::channel();
+
+        rcu.replace_with_drop_sink(2, |old_value| {
+            sender.send(old_value).unwrap();
+        });
+
+        let sunk = receiver.try_recv().unwrap();
+        assert!(Arc::ptr_eq(&sunk, &old));
+        assert!(receiver.try_recv().is_err());
+        assert_eq!(*unsafe { rcu.raw_read(&epoch_counters[0]) }, 2);
+    }
+
+    check::<arcu::atomic::Arcu<u32, _>>();
+    check::<arcu::rwlock::Arcu<u32, _>>();
+}
+
+#[test]
+fn replace_returning_old_ref() {
+    use arcu::rcu_ref::RcuRef;
+
+    fn check<R: Rcu<Item = (u32, &'static str), Pool = [Arc<EpochCounter>; 1]>>() {
+        let epoch_counters: [_; 1] = [Arc::new(EpochCounter::new())];
+        let rcu = R::new((1, "old"), epoch_counters.clone());
+
+        let old = rcu.replace_returning_old_ref((2, "new"));
+        let old_name = RcuRef::map(old, |(_, name)| name);
+        assert_eq!(*old_name, "old");
+
+        let new = unsafe { rcu.raw_read(&epoch_counters[0]) };
+        assert_eq!(*new, (2, "new"));
+    }
+
+    check::<arcu::atomic::Arcu<_, _>>();
+    check::<arcu::rwlock::Arcu<_, _>>();
+}
+
+#[cfg(feature = "thread_local_counter")]
+#[test]
+fn mixed_raw_and_safe() {
+    use arcu::epoch_counters::{register_epoch_counter, GlobalEpochCounterPool};
+
+    const WRITES: usize = 50;
+
+    let rcu = arcu::atomic::Arcu::new(0, GlobalEpochCounterPool);
+    let done = std::sync::atomic::AtomicBool::new(false);
+
+    std::thread::scope(|s| {
+        for _ in 0..2 {
+            // safe readers using the thread local epoch counter
+            s.spawn(|| {
+                let mut last = 0;
+                while !done.load(std::sync::atomic::Ordering::Acquire) {
+                    let current = *rcu.read();
+                    assert!(current >= last);
+                    last = current;
+                }
+            });
+
+            // raw readers using an explicitly registered epoch counter
+            s.spawn(|| {
+                let epoch_counter = Arc::new(EpochCounter::new());
+                register_epoch_counter(Arc::downgrade(&epoch_counter));
+
+                let mut last = 0;
+                while !done.load(std::sync::atomic::Ordering::Acquire) {
+                    let current = *unsafe { rcu.raw_read(&epoch_counter) };
+                    assert!(current >= last);
+                    last = current;
+                }
+            });
+        }
+
+        // writers alternating between replace and raw updates
+        let epoch_counter = Arc::new(EpochCounter::new());
+        register_epoch_counter(Arc::downgrade(&epoch_counter));
+        for value in 1..=WRITES {
+            if value % 2 == 0 {
+                rcu.replace(value);
+            } else {
+                unsafe { rcu.raw_try_update(|old| Some(Arc::new(old + 1)), &epoch_counter) };
+            }
+        }
+        done.store(true, std::sync::atomic::Ordering::Release);
+    });
+
+    assert_eq!(*rcu.read(), WRITES);
+}
+
+#[test]
+fn pinned_arcu() {
+    use arcu::pinned::PinnedArcu;
+    use core::{marker::PhantomPinned, pin::Pin};
+
+    struct SelfAware {
+        value: u32,
+        _pinned: PhantomPinned,
+    }
+
+    impl SelfAware {
+        fn value(self: Pin<&Self>) -> u32 {
+            self.value
+        }
+    }
+
+    let epoch_counters: [_; 1] = [Arc::new(EpochCounter::new())];
+    let rcu = PinnedArcu::new(
+        SelfAware {
+            value: 1,
+            _pinned: PhantomPinned,
+        },
+        epoch_counters.clone(),
+    );
+
+    let snapshot = unsafe { rcu.raw_read_pin(&epoch_counters[0]) };
+    let address = &*snapshot as *const SelfAware;
+    assert_eq!(snapshot.get().value(), 1);
+
+    let old = rcu.replace(SelfAware {
+        value: 2,
+        _pinned: PhantomPinned,
+    });
+    // the old value stays at the same address
+    assert_eq!(old.as_ref().value(), 1);
+    assert_eq!(&*old as *const SelfAware, address);
+
+    let current = unsafe { rcu.raw_read_pin(&epoch_counters[0]) }.into_pin_arc();
+    assert_eq!(current.as_ref().value(), 2);
+}
+
+#[cfg(feature = "thread_local_counter")]
+#[test]
+fn read_pinned_arc() {
+    use arcu::{epoch_counters::GlobalEpochCounterPool, pinned::PinnedArcu};
+    use core::{
+        future::Future,
+        pin::Pin,
+        task::{Context, Poll},
+    };
+    use std::{sync::Mutex, task::Wake};
+
+    struct NoopWaker;
+
+    impl Wake for NoopWaker {
+        fn wake(self: Arc<Self>) {}
+    }
+
+    type Task = Mutex<Pin<Box<dyn Future<Output = u32> + Send>>>;
+
+    fn task(value: u32) -> Task {
+        Mutex::new(Box::pin(async move { value }))
+    }
+
+    let rcu: PinnedArcu<Task, _> = PinnedArcu::new(task(1), GlobalEpochCounterPool);
+    let waker = Arc::new(NoopWaker).into();
+    let mut context = Context::from_waker(&waker);
+
+    let snapshot = rcu.read_pinned_arc();
+    rcu.replace(task(2));
+
+    // the snapshot keeps polling the task it read
+    let poll = snapshot.lock().unwrap().as_mut().poll(&mut context);
+    assert_eq!(poll, Poll::Ready(1));
+
+    let poll = rcu
+        .read_pinned_arc()
+        .lock()
+        .unwrap()
+        .as_mut()
+        .poll(&mut context);
+    assert_eq!(poll, Poll::Ready(2));
+}
+
+#[test]
+fn try_replace() {
+    use arcu::epoch_counters::{EpochCounterPool, WaitError};
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    /// gives up waiting while `give_up` is set
+    struct GiveUpPool {
+        epoch_counters: [Arc<EpochCounter>; 1],
+        give_up: Arc<AtomicBool>,
+    }
+
+    // Safety:
+    // - delegates to the array pool and only gives up instead of returning Ok
+    unsafe impl EpochCounterPool for GiveUpPool {
+        fn wait_for_epochs(&self) {
+            self.epoch_counters.wait_for_epochs()
+        }
+
+        fn try_wait_for_epochs(&self) -> Result<(), WaitError> {
+            if self.give_up.load(Ordering::Relaxed) {
+                return Err(WaitError);
+            }
+            self.epoch_counters.try_wait_for_epochs()
+        }
+    }
+
+    // infallible for pools that always succeed
+    let rcu = arcu::rwlock::Arcu::new(1, [Arc::new(EpochCounter::new())]);
+    assert_eq!(rcu.try_replace(2).ok().as_deref(), Some(&1));
+
+    let epoch_counter = Arc::new(EpochCounter::new());
+    let give_up = Arc::new(AtomicBool::new(false));
+    let rcu = arcu::atomic::Arcu::new(
+        1,
+        GiveUpPool {
+            epoch_counters: [epoch_counter.clone()],
+            give_up: give_up.clone(),
+        },
+    );
+    assert_eq!(rcu.try_replace(2).ok().as_deref(), Some(&1));
+
+    give_up.store(true, Ordering::Relaxed);
+    let error = rcu.try_replace(3).unwrap_err();
+    assert_eq!(error.cause(), WaitError);
+    // the new value is active even though the old one couldn't be reclaimed
+    assert_eq!(*unsafe { rcu.raw_read(&epoch_counter) }, 3);
+
+    let error = error.retry().unwrap_err();
+    give_up.store(false, Ordering::Relaxed);
+    assert_eq!(*error.retry().unwrap(), 2);
+
+    give_up.store(true, Ordering::Relaxed);
+    assert_eq!(*rcu.try_replace(4).unwrap_err().wait(), 3);
+}
+
+#[cfg(feature = "thread_local_counter")]
+#[test]
+fn nested_reads() {
+    use arcu::epoch_counters::GlobalEpochCounterPool;
+    use arcu::rcu_ref::RcuRef;
+
+    let rcu = arcu::atomic::Arcu::new(1, GlobalEpochCounterPool);
+
+    // map runs outside of the read critical section
+    let mapped = RcuRef::map(rcu.read(), |value| {
+        assert_eq!(*rcu.read(), *value);
+        value
+    });
+    assert_eq!(*mapped, 1);
+
+    // with_read_borrowed holds the read critical section while calling the function
+    let sum = rcu.with_read_borrowed(|outer| {
+        let inner = rcu.with_read_borrowed(|inner| *inner);
+        *outer + inner + *rcu.read()
+    });
+    assert_eq!(sum, 3);
+
+    // the thread's read critical section has been left, so writing doesn't block
+    assert_eq!(*rcu.replace(2), 1);
+    assert_eq!(*rcu.read(), 2);
+}
+
+#[cfg(feature = "thread_local_counter")]
+#[test]
+fn shared_snapshot() {
+    use arcu::epoch_counters::GlobalEpochCounterPool;
+    use arcu::rcu_ref::SharedSnapshot;
+
+    fn requires_clone<S: Clone>(state: &S) -> S {
+        state.clone()
+    }
+
+    let rcu = arcu::atomic::Arcu::new(String::from("old"), GlobalEpochCounterPool);
+    let snapshot = rcu.snapshot();
+    let cloned = requires_clone(&snapshot);
+    drop(rcu.replace(String::from("new")));
+
+    assert_eq!(&*cloned, "old");
+    assert_eq!(String::clone(&snapshot), "old");
+    assert_eq!(&*SharedSnapshot::into_arc(rcu.snapshot()), "new");
+}
+
+#[test]
+fn mutate_shared_snapshot() {
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::Mutex;
+
+    struct Stats {
+        hits: AtomicU64,
+        last: Mutex<Option<usize>>,
+    }
+
+    impl Stats {
+        fn new() -> Self {
+            Self {
+                hits: AtomicU64::new(0),
+                last: Mutex::new(None),
+            }
+        }
+    }
+
+    fn check<R: Rcu<Item = Stats, Pool = [Arc<EpochCounter>; 4]> + Sync>() {
+        let epoch_counters: [_; 4] = std::array::from_fn(|_| Arc::new(EpochCounter::new()));
+        let rcu = R::new(Stats::new(), epoch_counters.clone());
+
+        std::thread::scope(|scope| {
+            for (idx, epoch_counter) in epoch_counters.iter().enumerate() {
+                let rcu = &rcu;
+                scope.spawn(move || {
+                    for _ in 0..10 {
+                        // Safety: each thread uses its own epoch counter of the pool
+                        let stats = unsafe { rcu.raw_read(epoch_counter) };
+                        stats.hits.fetch_add(1, Ordering::Relaxed);
+                        *stats.last.lock().unwrap() = Some(idx);
+                    }
+                });
+            }
+        });
+
+        // the mutations are visible to all readers of the value, but don't carry over to its replacement
+        let old = rcu.replace(Stats::new());
+        assert_eq!(old.hits.load(Ordering::Relaxed), 40);
+        assert!(old.last.lock().unwrap().is_some());
+
+        let new = unsafe { rcu.raw_read(&epoch_counters[0]) };
+        assert_eq!(new.hits.load(Ordering::Relaxed), 0);
+    }
+
+    check::<arcu::atomic::Arcu<Stats, _>>();
+    check::<arcu::rwlock::Arcu<Stats, _>>();
+}
+
+#[cfg(feature = "stats")]
+#[test]
+fn update_stats() {
+    use arcu::atomic::UpdateStats;
+
+    let epoch_counters = [Arc::new(EpochCounter::new())];
+    let rcu = arcu::atomic::Arcu::new(1, epoch_counters.clone()).with_weak_updates(false);
+    assert_eq!(rcu.update_stats(), UpdateStats::default());
+
+    let stale = unsafe { rcu.raw_read(&epoch_counters[0]) };
+    unsafe { rcu.raw_try_update(|old| Some(Arc::new(old + 1)), &epoch_counters[0]) };
+    assert!(rcu
+        .compare_exchange_arc(&stale, Arc::new(3), false)
+        .is_err());
+
+    assert_eq!(
+        rcu.update_stats(),
+        UpdateStats {
+            attempts: 2,
+            failures: 1,
+            spurious_estimate: 0,
+        }
+    );
+}
+
+#[cfg(feature = "stats")]
+#[test]
+fn rwlock_conflict_count() {
+    let rcu = arcu::rwlock::Arcu::new(1, [Arc::new(EpochCounter::new())]);
+    assert_eq!(rcu.conflict_count(), 0);
+
+    let mut conflicted = false;
+    let old = rcu.try_update(|old| {
+        if !conflicted {
+            conflicted = true;
+            rcu.replace(old + 1);
+        }
+        Some(old * 10)
+    });
+    assert_eq!(old.as_deref(), Some(&2));
+    assert_eq!(rcu.conflict_count(), 1);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn dedicated_reclaiming_thread() {
+    use arcu::deferred::DeferredReclaimer;
+
+    struct DropThread(std::sync::mpsc::Sender<std::thread::ThreadId>);
+
+    impl Drop for DropThread {
+        fn drop(&mut self) {
+            let _ = self.0.send(std::thread::current().id());
+        }
+    }
+
+    let (sender, receiver) = std::sync::mpsc::channel();
+    let epoch_counters = [Arc::new(EpochCounter::new())];
+    let rcu = DeferredReclaimer::new(DropThread(sender.clone()), epoch_counters, Some(1))
+        .with_reclaim_on_write(false);
+
+    assert!(rcu.replace_deferred(DropThread(sender.clone())).is_ok());
+    // the reclaimable value isn't reclaimed by the write, so the limit is reached
+    drop(rcu.replace_deferred(DropThread(sender)).unwrap_err());
+    assert_eq!(receiver.try_recv(), Ok(std::thread::current().id()));
+    assert!(receiver.try_recv().is_err());
+
+    let reclaimer = std::thread::scope(|scope| {
+        scope
+            .spawn(|| {
+                assert_eq!(rcu.reclaim(), 1);
+                std::thread::current().id()
+            })
+            .join()
+            .unwrap()
+    });
+    assert_eq!(receiver.try_recv(), Ok(reclaimer));
+}
+
+#[cfg(feature = "thread_local_counter")]
+#[test]
+fn read_scope() {
+    use arcu::atomic::Arcu;
+    use arcu::epoch_counters::GlobalEpochCounterPool;
+
+    type Node = Arcu<u32, GlobalEpochCounterPool>;
+
+    let root = Arcu::new(
+        vec![
+            Node::new(1, GlobalEpochCounterPool),
+            Node::new(2, GlobalEpochCounterPool),
+        ],
+        GlobalEpochCounterPool,
+    );
+
+    std::thread::scope(|scope| {
+        let guard = arcu::atomic::read_scope();
+        let children: &Vec<Node> = guard.load(&root);
+        let first = guard.load(&children[0]);
+
+        // the writer has to wait for the guard, so the loaded value stays valid
+        let writer = scope.spawn(|| root.read()[0].replace(10));
+        assert_eq!(*first, 1);
+        assert_eq!(*guard.load(&children[1]), 2);
+
+        // other reads nest into the guard's read critical section
+        assert_eq!(*children[1].read(), 2);
+
+        drop(guard);
+        assert_eq!(*writer.join().unwrap(), 1);
+    });
+
+    assert_eq!(*root.read()[0].read(), 10);
+}
+
+#[cfg(feature = "thread_local_counter")]
+#[test]
+fn nested_arcu_teardown() {
+    use arcu::epoch_counters::GlobalEpochCounterPool;
+
+    fn check<Inner, Outer>()
+    where
+        Inner: Rcu<Item = u32, Pool = GlobalEpochCounterPool>,
+        Outer: Rcu<Item = Vec<Inner>, Pool = GlobalEpochCounterPool>,
+    {
+        let children = || {
+            (0..3)
+                .map(|i| Inner::new(i, GlobalEpochCounterPool))
+                .collect::<Vec<_>>()
+        };
+
+        let outer = Outer::new(children(), GlobalEpochCounterPool);
+        assert_eq!(*outer.read()[1].read(), 1);
+        outer.read()[1].replace(10);
+
+        // dropping the old children happens outside of the pool lock
+        drop(outer.replace(children()));
+
+        // and so does dropping a snapshot holding the last reference to the old children from within a read
+        let snapshot = outer.read();
+        drop(outer.replace(children()));
+        outer.with_read_borrowed(|children| {
+            assert_eq!(*children[2].read(), 2);
+            drop(snapshot);
+        });
+
+        drop(outer);
+    }
+
+    check::<arcu::atomic::Arcu<_, _>, arcu::atomic::Arcu<_, _>>();
+    check::<arcu::rwlock::Arcu<_, _>, arcu::rwlock::Arcu<_, _>>();
+    check::<arcu::atomic::Arcu<_, _>, arcu::rwlock::Arcu<_, _>>();
+}
+
+#[cfg(feature = "thread_local_counter")]
+#[test]
+fn replace_if_with() {
+    use arcu::epoch_counters::GlobalEpochCounterPool;
+
+    fn check<R: Rcu<Item = u32, Pool = GlobalEpochCounterPool>>(rcu: R) {
+        let mut built = 0;
+
+        // the new value isn't built when the condition fails
+        let old = rcu.replace_if_with(
+            |current| *current > 1,
+            |current| {
+                built += 1;
+                Arc::new(current * 10)
+            },
+        );
+        assert!(old.is_none());
+        assert_eq!(built, 0);
+
+        let old = rcu.replace_if_with(
+            |current| *current == 1,
+            |current| {
+                built += 1;
+                Arc::new(current * 10)
+            },
+        );
+        assert_eq!(old.as_deref(), Some(&1));
+        assert_eq!(built, 1);
+        assert_eq!(*rcu.read(), 10);
+    }
+
+    // the strong compare exchange doesn't fail spuriously, which would build the value again
+    check(arcu::atomic::Arcu::new(1, GlobalEpochCounterPool).with_weak_updates(false));
+    check(arcu::rwlock::Arcu::new(1, GlobalEpochCounterPool));
+}
+
+#[cfg(feature = "thread_local_counter")]
+#[test]
+fn get_element() {
+    use arcu::epoch_counters::GlobalEpochCounterPool;
+
+    fn check<R: Rcu<Item = Arc<[u32]>, Pool = GlobalEpochCounterPool> + Sync>() {
+        let rcu = R::new(Arc::<[u32]>::from([0, 1, 2]), GlobalEpochCounterPool);
+        assert_eq!(rcu.get(2).as_deref(), Some(&2));
+        assert!(rcu.get(3).is_none());
+
+        std::thread::scope(|scope| {
+            scope.spawn(|| {
+                for len in [1, 5, 2, 4] {
+                    rcu.replace(Arc::<[u32]>::from_iter(0..len));
+                }
+            });
+
+            for _ in 0..2 {
+                scope.spawn(|| {
+                    for index in [0, 3, 1, 4, 0] {
+                        // the element stays valid while the slice it points into is replaced
+                        if let Some(element) = rcu.get(index) {
+                            std::thread::yield_now();
+                            assert_eq!(*element, index as u32);
+                        }
+                    }
+                });
+            }
+        });
+
+        assert_eq!(rcu.get(3).as_deref(), Some(&3));
+        assert!(rcu.get(4).is_none());
+    }
+
+    check::<arcu::atomic::Arcu<_, _>>();
+    check::<arcu::rwlock::Arcu<_, _>>();
+}
+
+#[cfg(feature = "thread_local_counter")]
+#[test]
+fn read_arc() {
+    use arcu::{epoch_counters::GlobalEpochCounterPool, rcu_ref::RcuRef};
+
+    fn check<R: Rcu<Item = u32, Pool = GlobalEpochCounterPool>>() {
+        let rcu = R::new(1, GlobalEpochCounterPool);
+
+        let arc = Rcu::read_arc(&rcu);
+        assert_eq!(*arc, 1);
+        // the Rcu and the snapshot
+        assert_eq!(Arc::strong_count(&arc), 2);
+        drop(arc);
+
+        let arc = RcuRef::into_arc(rcu.read());
+        assert_eq!(Arc::strong_count(&arc), 2);
+
+        let old = rcu.replace(2);
+        assert!(Arc::ptr_eq(&old, &arc));
+        drop(old);
+        assert_eq!(Arc::strong_count(&arc), 1);
+    }
+
+    check::<arcu::atomic::Arcu<u32, _>>();
+    check::<arcu::rwlock::Arcu<u32, _>>();
+}
+
+#[cfg(feature = "thread_local_counter")]
+#[test]
+fn read_modify() {
+    use arcu::{epoch_counters::GlobalEpochCounterPool, ReadModify};
+
+    fn check<R: Rcu<Item = u32, Pool = GlobalEpochCounterPool> + Sync>(rcu: R) {
+        let result = rcu.read_modify(|current| ReadModify::Commit(current + 1, *current));
+        assert_eq!(result, 1);
+        assert_eq!(*rcu.read(), 2);
+
+        let result = rcu.read_modify(|current| ReadModify::<u32, _>::Abort(current * 10));
+        assert_eq!(result, 20);
+        assert_eq!(*rcu.read(), 2);
+
+        // retry re-reads until the closure is satisfied
+        let mut calls = 0;
+        let result = rcu.read_modify(|current| {
+            calls += 1;
+            if calls < 3 {
+                ReadModify::Retry
+            } else {
+                ReadModify::Commit(current * 3, calls)
+            }
+        });
+        assert_eq!(result, 3);
+        assert_eq!(*rcu.read(), 6);
+
+        // concurrent commits are retried, returning the result of the committed attempt
+        std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..4)
+                .map(|_| {
+                    scope.spawn(|| {
+                        (0..50)
+                            .map(|_| {
+                                rcu.read_modify(|current| ReadModify::Commit(current + 1, *current))
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+            let mut seen: Vec<u32> = handles
+                .into_iter()
+                .flat_map(|handle| handle.join().unwrap())
+                .collect();
+            seen.sort_unstable();
+            assert_eq!(seen, (6..206).collect::<Vec<_>>());
+        });
+        assert_eq!(*rcu.read(), 206);
+    }
+
+    // the strong compare exchange doesn't fail spuriously, which would call the closure again
+    check(arcu::atomic::Arcu::new(1, GlobalEpochCounterPool).with_weak_updates(false));
+    check(arcu::rwlock::Arcu::new(1, GlobalEpochCounterPool));
+}
+
+#[cfg(feature = "thread_local_counter")]
+#[test]
+fn try_update_bounded() {
+    use arcu::epoch_counters::GlobalEpochCounterPool;
+
+    fn check<R: Rcu<Item = u32, Pool = GlobalEpochCounterPool>>() {
+        let rcu = R::new(1, GlobalEpochCounterPool);
+
+        assert_eq!(
+            rcu.try_update_bounded(1, |old| Some(old + 1))
+                .unwrap()
+                .as_deref(),
+            Some(&1)
+        );
+        assert_eq!(rcu.try_update_bounded(1, |_| None::<u32>), Ok(None));
+
+        // no attempt may fail, so update is never called
+        let exhausted = rcu
+            .try_update_bounded(0, |_| -> Option<u32> { unreachable!() })
+            .unwrap_err();
+        assert_eq!(exhausted.attempts(), 0);
+        assert_eq!(exhausted.conflicts(), 0);
+
+        // a concurrent write on every attempt exhausts the attempts
+        let mut calls = 0;
+        let result = rcu.try_update_bounded(3, |old| {
+            calls += 1;
+            rcu.replace(old + 10);
+            Some(*old)
+        });
+        let exhausted = result.unwrap_err();
+        assert_eq!(exhausted.conflicts(), 3);
+        assert_eq!(exhausted.attempts(), calls);
+        assert_eq!(calls, 3);
+        assert_eq!(*rcu.read(), 32);
+    }
+
+    check::<arcu::atomic::Arcu<u32, _>>();
+    check::<arcu::rwlock::Arcu<u32, _>>();
+}
+
+#[cfg(feature = "thread_local_counter")]
+#[test]
+fn try_update_indexed() {
+    use arcu::epoch_counters::GlobalEpochCounterPool;
+
+    fn check<R: Rcu<Item = u32, Pool = GlobalEpochCounterPool>>() {
+        let rcu = R::new(1, GlobalEpochCounterPool);
+
+        let mut attempts = Vec::new();
+        let old = rcu.try_update_indexed(|old, attempt| {
+            attempts.push(attempt);
+            // make the first attempt conflict
+            if attempt == 0 {
+                rcu.replace(*old + 1);
+            }
+            Some(Arc::new(*old * 10))
+        });
+        assert_eq!(old.as_deref(), Some(&2));
+        // spurious compare exchange failures repeat an attempt without advancing the attempt number
+        attempts.dedup();
+        assert_eq!(attempts, [0, 1]);
+        assert_eq!(*rcu.read(), 20);
+
+        // give up after a number of attempts
+        let old = rcu.try_update_indexed(|old, attempt| {
+            rcu.replace(*old + 1);
+            (attempt < 2).then(|| Arc::new(0))
+        });
+        assert!(old.is_none());
+        assert_eq!(*rcu.read(), 23);
+    }
+
+    check::<arcu::atomic::Arcu<u32, _>>();
+    check::<arcu::rwlock::Arcu<u32, _>>();
+}
+
+#[test]
+fn dead_reader_does_not_block_writers() {
+    use std::sync::{Mutex, Weak};
+
+    let epoch_counters = Mutex::new(Vec::<Weak<EpochCounter>>::new());
+    let pool = || epoch_counters.lock().unwrap().clone();
+    let rcu = arcu::atomic::Arcu::new(1, pool);
+
+    std::thread::scope(|scope| {
+        scope.spawn(|| {
+            let epoch_counter = Arc::new(EpochCounter::new());
+            epoch_counters
+                .lock()
+                .unwrap()
+                .push(Arc::downgrade(&epoch_counter));
+
+            // the reader dies within the read critical section, leaving its epoch counter odd
+            let session = unsafe { rcu.enter_read(&epoch_counter) };
+            assert_eq!(*session.load(), 1);
+            std::mem::forget(session);
+        });
+    });
+
+    // the dead reader's epoch counter has been dropped, so the writer doesn't wait for it
+    assert_eq!(*rcu.replace(2), 1);
+}
+
+#[cfg(feature = "thread_local_counter")]
+#[test]
+fn sharded_read_arcu() {
+    use arcu::sharded::ShardedReadArcu;
+
+    let rcu = ShardedReadArcu::new(0, 3);
+    assert_eq!(rcu.shards(), 3);
+    assert_eq!(*rcu.read(), 0);
+
+    std::thread::scope(|scope| {
+        for _ in 0..6 {
+            scope.spawn(|| {
+                let mut last = 0;
+                for _ in 0..20 {
+                    // each thread reads from its own shard, which never goes back to an older value
+                    let value = *rcu.read();
+                    assert!(value >= last);
+                    last = value;
+                }
+            });
+        }
+
+        scope.spawn(|| {
+            for value in 1..=5 {
+                assert_eq!(*rcu.replace(value), value - 1);
+            }
+        });
+    });
+
+    let snapshot = rcu.read();
+    assert_eq!(*rcu.replace(6), 5);
+    assert_eq!(*snapshot, 5);
+    assert_eq!(*rcu.read(), 6);
+}
+
+#[test]
+fn concurrent_replace_does_not_leak() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    const THREADS: usize = 4;
+    const WRITES: usize = 10;
+
+    struct DropCounter(Arc<AtomicUsize>);
+
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    fn check<R: Rcu<Item = DropCounter, Pool = [Arc<EpochCounter>; THREADS]> + Sync>() {
+        let dropped = Arc::new(AtomicUsize::new(0));
+        let epoch_counters: [_; THREADS] = std::array::from_fn(|_| Arc::new(EpochCounter::new()));
+        let rcu = R::new(DropCounter(dropped.clone()), epoch_counters.clone());
+
+        std::thread::scope(|scope| {
+            for epoch_counter in &epoch_counters {
+                let (rcu, dropped) = (&rcu, &dropped);
+                scope.spawn(move || {
+                    for _ in 0..WRITES {
+                        drop(rcu.replace(DropCounter(dropped.clone())));
+                        drop(unsafe { rcu.raw_read(epoch_counter) });
+                    }
+                });
+            }
+        });
+
+        // every value but the active one has been dropped
+        assert_eq!(dropped.load(Ordering::Relaxed), THREADS * WRITES);
+        drop(rcu);
+        assert_eq!(dropped.load(Ordering::Relaxed), THREADS * WRITES + 1);
+    }
+
+    check::<arcu::atomic::Arcu<_, _>>();
+    check::<arcu::rwlock::Arcu<_, _>>();
+}
+
+#[test]
+fn rwlock_panic_isolation() {
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    struct PanickingDisplay(u32);
+
+    impl std::fmt::Display for PanickingDisplay {
+        fn fmt(&self, _f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            panic!("display failed")
+        }
+    }
+
+    let rcu = arcu::rwlock::Arcu::new(PanickingDisplay(1), [Arc::new(EpochCounter::new())]);
+
+    // panics in user code called by the Arcu don't affect later reads and writes
+    assert!(catch_unwind(AssertUnwindSafe(|| rcu.to_string())).is_err());
+    assert!(catch_unwind(AssertUnwindSafe(|| {
+        rcu.try_update(|_| -> Option<PanickingDisplay> { panic!("update failed") })
+    }))
+    .is_err());
+
+    // the conversion of the new value is user code as well
+    struct PanickingInto;
+
+    impl From<PanickingInto> for Arc<PanickingDisplay> {
+        fn from(_: PanickingInto) -> Self {
+            panic!("into failed")
+        }
+    }
+
+    assert!(catch_unwind(AssertUnwindSafe(|| rcu.replace(PanickingInto))).is_err());
+
+    assert!(!rcu.is_poisoned());
+    assert_eq!(rcu.read().0, 1);
+    assert_eq!(rcu.replace(PanickingDisplay(2)).0, 1);
+    assert_eq!(rcu.read().0, 2);
+}
+
+#[test]
+fn rwlock_update_in_place() {
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static CLONES: AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(Debug)]
+    struct CloneCounter(u32);
+
+    impl Clone for CloneCounter {
+        fn clone(&self) -> Self {
+            CLONES.fetch_add(1, Ordering::Relaxed);
+            CloneCounter(self.0)
+        }
+    }
+
+    let rcu = arcu::rwlock::Arcu::new(CloneCounter(1), [Arc::new(EpochCounter::new())]);
+
+    // unique, updated without cloning
+    rcu.update_in_place(|value| value.0 += 1);
+    assert_eq!(CLONES.load(Ordering::Relaxed), 0);
+    assert_eq!(rcu.read().0, 2);
+
+    // a reader holds the snapshot, which stays unchanged
+    let snapshot = rcu.read();
+    rcu.update_in_place(|value| value.0 += 1);
+    assert_eq!(CLONES.load(Ordering::Relaxed), 1);
+    assert_eq!(snapshot.0, 2);
+    assert_eq!(rcu.read().0, 3);
+
+    // a panic while holding the write lock poisons it
+    assert!(rcu.try_read_checked().is_ok());
+    assert!(catch_unwind(AssertUnwindSafe(
+        || rcu.update_in_place(|_| panic!("update failed"))
+    ))
+    .is_err());
+    assert!(rcu.is_poisoned());
+    assert_eq!(
+        rcu.try_read_checked().unwrap_err(),
+        arcu::BackendError::Poisoned
+    );
+    assert_eq!(rcu.read().0, 3);
+}
+
+#[cfg(feature = "thread_local_counter")]
+#[test]
+fn try_read_checked() {
+    use arcu::epoch_counters::GlobalEpochCounterPool;
+
+    fn check<R: Rcu<Item = u32, Pool = GlobalEpochCounterPool>>() {
+        let rcu = R::new(1, GlobalEpochCounterPool);
+        assert_eq!(rcu.try_read_checked().as_deref(), Ok(&1));
+        rcu.replace(2);
+        assert_eq!(rcu.try_read_checked().as_deref(), Ok(&2));
+    }
+
+    check::<arcu::atomic::Arcu<u32, _>>();
+    check::<arcu::rwlock::Arcu<u32, _>>();
+}
+
+#[cfg(feature = "thread_local_counter")]
+#[test]
+fn with_read_raw() {
+    use arcu::epoch_counters::GlobalEpochCounterPool;
+    use std::ffi::c_void;
+
+    extern "C" fn add_value(ctx: *mut c_void, value: *const u32) {
+        // Safety: the test passes a pointer to a u32 as ctx, the value is valid during the callback
+        unsafe { *ctx.cast::<u32>() += *value };
+    }
+
+    fn check<R: Rcu<Item = u32, Pool = GlobalEpochCounterPool>>() {
+        let rcu = R::new(2, GlobalEpochCounterPool);
+        let mut sum = 1u32;
+        rcu.with_read_raw(std::ptr::addr_of_mut!(sum).cast(), add_value);
+        assert_eq!(sum, 3);
+    }
+
+    check::<arcu::atomic::Arcu<u32, _>>();
+    check::<arcu::rwlock::Arcu<u32, _>>();
+}
+
+#[test]
+#[cfg_attr(miri, ignore = "miri can't spawn processes")]
+fn rwlock_contention_is_quiet() {
+    const CHILD: &str = "ARCU_CONTENTION_CHILD";
+
+    if std::env::var_os(CHILD).is_some() {
+        let rcu = arcu::rwlock::Arcu::new(0, [Arc::new(EpochCounter::new())]);
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| {
+                    for _ in 0..100 {
+                        rcu.try_update(|old| {
+                            // widen the window for conflicting updates
+                            std::thread::yield_now();
+                            Some(old + 1)
+                        });
+                    }
+                });
+            }
+        });
+        assert_eq!(*rcu.read(), 400);
+        return;
+    }
+
+    // run the contended updates in a child process, as the test harness captures the output of this one
+    let output = std::process::Command::new(std::env::current_exe().unwrap())
+        .args(["--exact", "rwlock_contention_is_quiet", "--nocapture"])
+        .env(CHILD, "1")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let unexpected = stdout
+        .lines()
+        .filter(|line| !line.is_empty())
+        .filter(|line| !line.starts_with("running ") && !line.starts_with("test "))
+        .collect::<Vec<_>>();
+    assert!(unexpected.is_empty(), "unexpected output: {unexpected:?}");
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn sharded_pool() {
+    use arcu::epoch_counters::ShardedPool;
+
+    let pool = ShardedPool::<4>::new();
+    let rcu = arcu::atomic::Arcu::new(0, pool.clone());
+
+    std::thread::scope(|scope| {
+        for _ in 0..8 {
+            scope.spawn(|| {
+                let epoch_counter = Arc::new(EpochCounter::new());
+                pool.register_epoch_counter(Arc::downgrade(&epoch_counter));
+                for _ in 0..10 {
+                    let value = unsafe { rcu.raw_read(&epoch_counter) };
+                    assert!(*value <= 10);
+                }
+            });
+        }
+
+        scope.spawn(|| {
+            for value in 1..=10 {
+                assert_eq!(*rcu.replace(value), value - 1);
+            }
+        });
+    });
+
+    use arcu::epoch_counters::EpochCounterPool;
+
+    let epoch_counter = Arc::new(EpochCounter::new());
+    assert_eq!(pool.contains_epoch_counter(&epoch_counter), Some(false));
+    pool.register_epoch_counter(Arc::downgrade(&epoch_counter));
+    assert_eq!(pool.contains_epoch_counter(&epoch_counter), Some(true));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn contains_epoch_counter_tags() {
+    use arcu::epoch_counters::{EpochCounterPool, LocalEpochCounterPool};
+
+    let pool_a = LocalEpochCounterPool::new();
+    let pool_b = LocalEpochCounterPool::new();
+
+    let epoch_counter = Arc::new(EpochCounter::new());
+    pool_a.register(Arc::downgrade(&epoch_counter));
+    assert_eq!(pool_a.contains_epoch_counter(&epoch_counter), Some(true));
+    assert_eq!(
+        pool_a.clone().contains_epoch_counter(&epoch_counter),
+        Some(true)
+    );
+    assert_eq!(pool_b.contains_epoch_counter(&epoch_counter), Some(false));
+
+    // an epoch counter registered with multiple pools can't be told apart by its tag
+    pool_b.register(Arc::downgrade(&epoch_counter));
+    assert_eq!(pool_a.contains_epoch_counter(&epoch_counter), None);
+    assert_eq!(pool_b.contains_epoch_counter(&epoch_counter), None);
+}
+
+#[cfg(feature = "thread_local_counter")]
+#[test]
+fn cache_load() {
+    use arcu::cache::Cache;
+    use arcu::epoch_counters::GlobalEpochCounterPool;
+
+    let rcu = Arc::new(arcu::atomic::Arcu::new(1, GlobalEpochCounterPool));
+    let mut cache = Cache::new(rcu.clone());
+
+    let first = Arc::clone(cache.load());
+    assert_eq!(*first, 1);
+    // unchanged, so the cached arc is returned
+    assert!(Arc::ptr_eq(cache.load(), &first));
+
+    rcu.replace(2);
+    assert_eq!(**cache.load(), 2);
+
+    // a borrowed Arcu works as well
+    let mut cache = Cache::new(&*rcu);
+    assert_eq!(**cache.load(), 2);
+    assert!(std::ptr::eq(*cache.rcu(), &*rcu));
+}
+
+#[test]
+#[cfg_attr(
+    miri,
+    ignore = "relies on racing threads, which is slow and rare under miri"
+)]
+fn concurrent_epoch_counter_misuse() {
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    // violating the contract of raw_read by using one epoch counter from multiple threads
+    // may go unnoticed, but if it is noticed it must fail loudly with an informative panic
+    let epoch_counter = Arc::new(EpochCounter::new());
+    let rcu = arcu::atomic::Arcu::new(0, [epoch_counter.clone()]);
+
+    let start = std::sync::Barrier::new(4);
+    let results = std::thread::scope(|scope| {
+        let threads = (0..4)
+            .map(|_| {
+                scope.spawn(|| {
+                    start.wait();
+                    catch_unwind(AssertUnwindSafe(|| {
+                        for _ in 0..100_000 {
+                            drop(unsafe { rcu.raw_read(&epoch_counter) });
+                        }
+                    }))
+                })
+            })
+            .collect::<Vec<_>>();
+        threads
+            .into_iter()
+            .map(|thread| thread.join().unwrap())
+            .collect::<Vec<_>>()
+    });
+
+    for result in results {
+        if let Err(panic) = result {
+            let message = panic
+                .downcast_ref::<String>()
+                .map(String::as_str)
+                .or_else(|| panic.downcast_ref::<&str>().copied())
+                .unwrap_or_default();
+            assert!(
+                message.contains("must not be used concurrently"),
+                "unexpected panic: {message}"
+            );
+        }
+    }
+
+    // the epoch counter may be left in an inconsistent state, so the Arcu is leaked instead of writing to it
+    std::mem::forget(rcu);
+}
+
+#[cfg(all(feature = "bincode", feature = "thread_local_counter"))]
+#[test]
+fn bincode_snapshot() {
+    use arcu::epoch_counters::GlobalEpochCounterPool;
+
+    fn check<R: Rcu<Item = (u32, String), Pool = GlobalEpochCounterPool>>() {
+        let source = R::new((1, String::from("one")), GlobalEpochCounterPool);
+        let target = R::new((0, String::new()), GlobalEpochCounterPool);
+
+        let bytes = source.encode_snapshot().unwrap();
+        let old = target.decode_replace(&bytes).unwrap();
+        assert_eq!(*old, (0, String::new()));
+        assert_eq!(*target.read(), (1, String::from("one")));
+
+        // invalid bytes leave the Rcu unchanged
+        assert!(target.decode_replace(&bytes[..2]).is_err());
+        assert_eq!(*target.read(), (1, String::from("one")));
+    }
+
+    check::<arcu::atomic::Arcu<_, _>>();
+    check::<arcu::rwlock::Arcu<_, _>>();
+}
+
+#[cfg(all(feature = "bincode", feature = "thread_local_counter"))]
+#[test]
+fn serde_transparent() {
+    use arcu::epoch_counters::GlobalEpochCounterPool;
+
+    fn check<R>()
+    where
+        R: Rcu<Item = (u32, String), Pool = GlobalEpochCounterPool>
+            + serde::Serialize
+            + serde::de::DeserializeOwned,
+    {
+        let value = (1, String::from("one"));
+        let rcu = R::new(value.clone(), GlobalEpochCounterPool);
+
+        // the Rcu and its snapshots serialize like the value itself
+        let bytes = bincode::serialize(&rcu).unwrap();
+        assert_eq!(bytes, bincode::serialize(&value).unwrap());
+        assert_eq!(bincode::serialize(&rcu.read()).unwrap(), bytes);
+
+        let decoded = bincode::deserialize::<R>(&bytes).unwrap();
+        assert_eq!(*decoded.read(), value);
+        rcu.replace((2, String::from("two")));
+        assert_eq!(*decoded.read(), value);
+    }
+
+    check::<arcu::atomic::Arcu<_, _>>();
+    check::<arcu::rwlock::Arcu<_, _>>();
+
+    let name = arcu::atomic::Arcu::<str, _>::new(Arc::from("name"), GlobalEpochCounterPool);
+    assert_eq!(
+        bincode::serialize(&name).unwrap(),
+        bincode::serialize("name").unwrap()
+    );
+}
+
+#[cfg(feature = "thread_local_counter")]
+#[test]
+fn unsized_values() {
+    use arcu::epoch_counters::GlobalEpochCounterPool;
+
+    fn check<R: Rcu<Item = [u32], Pool = GlobalEpochCounterPool> + Sync>() {
+        let rcu = R::new(Arc::from([1, 2, 3]), GlobalEpochCounterPool);
+        assert_eq!(*rcu.read(), [1, 2, 3]);
+
+        let old = rcu.replace(Arc::from([4, 5]));
+        assert_eq!(*old, [1, 2, 3]);
+
+        rcu.try_update(|old| Some(old.iter().map(|value| value * 2).collect::<Arc<[u32]>>()));
+        assert_eq!(*rcu.read(), [8, 10]);
+
+        std::thread::scope(|scope| {
+            for _ in 0..2 {
+                scope.spawn(|| {
+                    for _ in 0..20 {
+                        let snapshot = rcu.read();
+                        assert_eq!(snapshot[..2], [8, 10]);
+                    }
+                });
+            }
+            for writer in 0..2 {
+                let rcu = &rcu;
+                scope.spawn(move || {
+                    for _ in 0..10 {
+                        rcu.try_update(|old| {
+                            Some(old.iter().copied().chain([writer]).collect::<Arc<[u32]>>())
+                        });
+                    }
+                });
+            }
+        });
+        assert_eq!(rcu.read().len(), 2 + 20);
+    }
+
+    check::<arcu::atomic::Arcu<[u32], _>>();
+    check::<arcu::rwlock::Arcu<[u32], _>>();
+
+    let name = arcu::atomic::Arcu::<str, _>::new(Arc::from("old"), GlobalEpochCounterPool);
+    assert_eq!(name.to_string(), "old");
+    assert_eq!(&*name.replace_reclaimed("new").reclaim(), "old");
+    assert_eq!(arcu::arcu_read!(let name = &name; { name.len() }), 3);
+    assert_eq!(&*Arc::<str>::from(name), "new");
+}
+
+#[cfg(feature = "thread_local_counter")]
+#[test]
+fn clone_snapshots_value() {
+    use arcu::epoch_counters::{GlobalEpochCounterPool, LocalEpochCounterPool};
+
+    let rcu = arcu::atomic::Arcu::new(1, GlobalEpochCounterPool);
+    let clone = rcu.clone();
+    assert!(Arc::ptr_eq(&rcu.read_arc(), &clone.read_arc()));
+
+    // the clones are independent afterwards
+    assert_eq!(*rcu.replace(2), 1);
+    assert_eq!(*clone.read(), 1);
+    assert_eq!(*clone.replace(3), 1);
+    assert_eq!(*rcu.read(), 2);
+
+    let name = arcu::atomic::Arcu::<str, _>::new(Arc::from("name"), GlobalEpochCounterPool);
+    assert_eq!(name.clone().to_string(), "name");
+
+    let pool = LocalEpochCounterPool::new();
+    let rcu = arcu::atomic::Arcu::new(10, pool.clone());
+    let clone = rcu.clone();
+    assert_eq!(*rcu.replace(11), 10);
+    assert_eq!(*clone.read(), 10);
+    assert_eq!(*clone.replace(12), 10);
+    assert_eq!(*rcu.read(), 11);
+}
@@ -0,0 +1,21 @@
+#![forbid(unsafe_code)]
+
+use alloc::sync::Arc;
+
+use arcu::{epoch_counters::EpochCounter, rwlock::Arcu, Rcu};
+
+extern crate alloc;
+
+#[test]
+fn rwlock_without_unsafe() {
+    let rcu = Arcu::new(1, [] as [Arc<EpochCounter>; 0]);
+    assert_eq!(*rcu.read(), 1);
+
+    let old = rcu.replace(2);
+    assert_eq!(*old, 1);
+
+    let old = rcu.try_update(|old| Some(old + 1));
+    assert_eq!(old.as_deref(), Some(&2));
+    assert!(rcu.try_update(|_| None::<u32>).is_none());
+    assert_eq!(*rcu.read(), 3);
+}
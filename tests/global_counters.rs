@@ -0,0 +1,56 @@
+//! Kept separate from the other tests, as these observe the global counters
+
+#![cfg(feature = "thread_local_counter")]
+
+extern crate alloc;
+
+use arcu::{
+    epoch_counters::{
+        global_counters, global_counters_capacity, reserve_global_counters, GlobalEpochCounterPool,
+    },
+    Rcu,
+};
+
+#[test]
+fn reserve_global_counters_avoids_reallocation() {
+    const THREADS: usize = 32;
+
+    reserve_global_counters(THREADS);
+    let capacity = global_counters_capacity();
+    assert!(capacity >= global_counters().len() + THREADS);
+
+    let rcu = arcu::atomic::Arcu::new(0u32, GlobalEpochCounterPool);
+    std::thread::scope(|scope| {
+        for _ in 0..THREADS {
+            // each thread registers its epoch counter on the first read
+            scope.spawn(|| assert_eq!(*rcu.read(), 0));
+        }
+    });
+
+    assert_eq!(global_counters_capacity(), capacity);
+}
+
+#[test]
+fn prune_global_epoch_counters() {
+    use alloc::sync::Arc;
+    use arcu::epoch_counters::{prune_global_epoch_counters, register_epoch_counter, EpochCounter};
+
+    let epoch_counters: Vec<_> = (0..100).map(|_| Arc::new(EpochCounter::new())).collect();
+    for epoch_counter in &epoch_counters {
+        register_epoch_counter(Arc::downgrade(epoch_counter));
+    }
+    let addresses: Vec<_> = epoch_counters.iter().map(Arc::as_ptr).collect();
+    drop(epoch_counters);
+
+    let is_ours = |elem: &alloc::sync::Weak<EpochCounter>| addresses.contains(&elem.as_ptr());
+    assert_eq!(
+        global_counters()
+            .iter()
+            .filter(|elem| is_ours(elem))
+            .count(),
+        100
+    );
+
+    assert!(prune_global_epoch_counters() >= 100);
+    assert!(!global_counters().iter().any(is_ours));
+}
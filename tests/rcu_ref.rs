@@ -0,0 +1,234 @@
+use alloc::sync::Arc;
+
+use arcu::rcu_ref::RcuRef;
+
+extern crate alloc;
+
+#[test]
+fn get() {
+    let vec = RcuRef::new(Arc::new(vec![1, 2, 3]));
+    assert_eq!(RcuRef::get(RcuRef::clone(&vec), 1).as_deref(), Some(&2));
+    assert!(RcuRef::get(RcuRef::clone(&vec), 3).is_none());
+
+    let slice = RcuRef::map(vec, Vec::as_slice);
+    assert_eq!(RcuRef::get(RcuRef::clone(&slice), 2).as_deref(), Some(&3));
+    assert!(RcuRef::get(slice, 3).is_none());
+}
+
+#[test]
+fn into_arc() {
+    let arc = Arc::new((1, 2));
+
+    let second = RcuRef::map(RcuRef::new(Arc::clone(&arc)), |pair| &pair.1);
+    assert_eq!(Arc::strong_count(&arc), 2);
+
+    // drops the mapping, returning the root
+    let root = RcuRef::into_arc(second);
+    assert!(Arc::ptr_eq(&root, &arc));
+    assert_eq!(Arc::strong_count(&arc), 2);
+}
+
+#[test]
+fn try_map_or_self() {
+    let arc = Arc::new((Some(1), None::<u32>));
+
+    let second = RcuRef::try_map_or_self(RcuRef::new(Arc::clone(&arc)), |pair| pair.1.as_ref());
+    let original = second.unwrap_err();
+    // the original is handed back without touching the strong count
+    assert_eq!(Arc::strong_count(&arc), 2);
+
+    let first = RcuRef::try_map_or_self(original, |pair| pair.0.as_ref()).unwrap();
+    assert_eq!(*first, 1);
+    assert_eq!(Arc::strong_count(&arc), 2);
+}
+
+#[cfg(feature = "stable_deref")]
+#[test]
+fn stable_deref() {
+    fn address<P: stable_deref_trait::StableDeref>(ptr: &P) -> *const P::Target {
+        &**ptr
+    }
+
+    let vec = RcuRef::new(Arc::new(vec![1, 2, 3]));
+    let slice = RcuRef::map(vec, Vec::as_slice);
+    let before = address(&slice);
+    let moved = Box::new(slice);
+    assert_eq!(address(&*moved), before);
+}
+
+#[test]
+fn iter() {
+    let vec = RcuRef::new(Arc::new(vec![1, 2, 3]));
+    let mut iter = RcuRef::iter(vec);
+    assert_eq!(iter.len(), 3);
+    assert_eq!(iter.next().as_deref(), Some(&1));
+    assert_eq!(iter.map(|element| *element).collect::<Vec<_>>(), [2, 3]);
+}
+
+#[test]
+fn panicking_projection() {
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+    struct DropCounter(u32);
+
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            DROPPED.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    let arc = Arc::new(DropCounter(1));
+
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        RcuRef::map(RcuRef::new(Arc::clone(&arc)), |_| -> &u32 { panic!("map") })
+    }));
+    assert!(result.is_err());
+    assert_eq!(Arc::strong_count(&arc), 1);
+
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        RcuRef::try_map(RcuRef::new(Arc::clone(&arc)), |_| -> Option<&u32> {
+            panic!("try_map")
+        })
+    }));
+    assert!(result.is_err());
+    assert_eq!(Arc::strong_count(&arc), 1);
+
+    // the projections still work and release the arc afterwards
+    let mapped = RcuRef::map(RcuRef::new(Arc::clone(&arc)), |counter| &counter.0);
+    assert_eq!(*mapped, 1);
+    assert_eq!(Arc::strong_count(&arc), 2);
+    drop(mapped);
+    assert_eq!(Arc::strong_count(&arc), 1);
+
+    assert_eq!(DROPPED.load(Ordering::Relaxed), 0);
+    drop(arc);
+    assert_eq!(DROPPED.load(Ordering::Relaxed), 1);
+}
+
+#[test]
+fn changed_from() {
+    let previous = RcuRef::new(Arc::new((1, 2)));
+
+    let same = RcuRef::clone(&previous);
+    assert!(!RcuRef::changed_from(&same, &previous));
+    assert!(!RcuRef::value_changed_from(&same, &previous));
+
+    // a write replacing the value with an equal one
+    let equal = RcuRef::new(Arc::new((1, 2)));
+    assert!(RcuRef::changed_from(&equal, &previous));
+    assert!(!RcuRef::value_changed_from(&equal, &previous));
+
+    // a write only changing the part the refs aren't projected to
+    let other_part = RcuRef::new(Arc::new((1, 3)));
+    assert!(!RcuRef::value_changed_from(
+        &RcuRef::map(RcuRef::clone(&other_part), |value| &value.0),
+        &RcuRef::map(RcuRef::clone(&previous), |value| &value.0),
+    ));
+    assert!(RcuRef::value_changed_from(&other_part, &previous));
+}
+
+#[cfg(feature = "debug_snapshots")]
+#[test]
+fn snapshot_holders() {
+    use arcu::rcu_ref::snapshot_holders;
+
+    fn holders_of(arc: &Arc<u32>) -> Vec<std::thread::ThreadId> {
+        let addr = Arc::as_ptr(arc) as usize;
+        snapshot_holders()
+            .into_iter()
+            .find(|(holder_addr, _)| *holder_addr == addr)
+            .map_or_else(Vec::new, |(_, threads)| threads)
+    }
+
+    let arc = Arc::new(1);
+    let current = std::thread::current().id();
+
+    let reference = RcuRef::new(Arc::clone(&arc));
+    let projected = RcuRef::map(RcuRef::clone(&reference), |value| value);
+    assert_eq!(holders_of(&arc), [current, current]);
+
+    let other = std::thread::scope(|scope| {
+        scope
+            .spawn(|| {
+                let held = RcuRef::new(Arc::clone(&arc));
+                let other = std::thread::current().id();
+                let mut holders = holders_of(&arc);
+                holders.sort_by_key(|thread| *thread == other);
+                assert_eq!(holders, [current, current, other]);
+                drop(held);
+                other
+            })
+            .join()
+            .unwrap()
+    });
+    assert!(!holders_of(&arc).contains(&other));
+
+    drop(reference);
+    assert_eq!(holders_of(&arc), [current]);
+    drop(projected);
+    assert!(holders_of(&arc).is_empty());
+}
+
+#[test]
+fn value_eq() {
+    let first = RcuRef::new(Arc::new((1, 2)));
+    let second = RcuRef::new(Arc::new((3, 2)));
+
+    // equal values from different epochs compare equal
+    let first_second = RcuRef::map(RcuRef::clone(&first), |pair| &pair.1);
+    let second_second = RcuRef::map(RcuRef::clone(&second), |pair| &pair.1);
+    assert_eq!(first_second, second_second);
+    assert!(!RcuRef::same_epoch(&first_second, &second_second));
+
+    assert_ne!(first, second);
+    assert_eq!(first, RcuRef::clone(&first));
+}
+
+#[test]
+fn value_ord_and_hash() {
+    use std::collections::{BTreeMap, HashSet};
+
+    // the root isn't comparable, only the projected values
+    struct Root(&'static str, u32);
+
+    let refs = [Root("c", 3), Root("a", 1), Root("b", 2), Root("a", 4)]
+        .into_iter()
+        .map(|root| RcuRef::map(RcuRef::new(Arc::new(root)), |root| &root.0))
+        .collect::<Vec<_>>();
+
+    let by_content = refs
+        .iter()
+        .map(|name| (RcuRef::clone(name), RcuRef::get_root(name).1))
+        .collect::<BTreeMap<_, _>>();
+    assert_eq!(
+        by_content
+            .iter()
+            .map(|(name, value)| (**name, *value))
+            .collect::<Vec<_>>(),
+        [("a", 4), ("b", 2), ("c", 3)]
+    );
+
+    let distinct = refs.iter().map(RcuRef::clone).collect::<HashSet<_>>();
+    assert_eq!(distinct.len(), 3);
+}
+
+#[test]
+fn map_split() {
+    let arc = Arc::new((String::from("name"), vec![1, 2, 3]));
+
+    let (name, values) = RcuRef::map_split(RcuRef::new(Arc::clone(&arc)), |pair| {
+        (pair.0.as_str(), pair.1.as_slice())
+    });
+    assert_eq!(&*name, "name");
+    assert_eq!(&*values, [1, 2, 3]);
+    assert!(RcuRef::same_epoch(&name, &values));
+    assert_eq!(Arc::strong_count(&arc), 3);
+
+    // each half keeps the value alive on its own
+    drop(arc);
+    drop(name);
+    assert_eq!(&*values, [1, 2, 3]);
+}
@@ -0,0 +1,51 @@
+//! A coarse guard against catastrophic regressions of the read path, e.g. a lock or syscall sneaking in
+//!
+//! This is not a benchmark, the threshold is orders of magnitude above the expected latency.
+//! Set `ARCU_SKIP_TIMING` to skip it, e.g. on slow or heavily shared CI machines.
+
+#![cfg(feature = "thread_local_counter")]
+
+use std::time::{Duration, Instant};
+
+use arcu::{epoch_counters::GlobalEpochCounterPool, Rcu};
+
+const BATCH: u32 = 10_000;
+const BATCHES: usize = 21;
+
+fn median_read_latency<R: Rcu<Item = u64, Pool = GlobalEpochCounterPool>>() -> Duration {
+    let rcu = R::new(42, GlobalEpochCounterPool);
+
+    // warm up, including registering the thread local epoch counter
+    for _ in 0..BATCH {
+        std::hint::black_box(rcu.read());
+    }
+
+    let mut batches = (0..BATCHES)
+        .map(|_| {
+            let start = Instant::now();
+            for _ in 0..BATCH {
+                std::hint::black_box(rcu.read());
+            }
+            start.elapsed() / BATCH
+        })
+        .collect::<Vec<_>>();
+    batches.sort();
+    batches[BATCHES / 2]
+}
+
+#[test]
+#[cfg_attr(miri, ignore = "timing is meaningless under miri")]
+fn uncontended_read_latency() {
+    if std::env::var_os("ARCU_SKIP_TIMING").is_some() {
+        return;
+    }
+
+    // an uncontended read takes tens of nanoseconds, even unoptimized
+    let threshold = Duration::from_micros(5);
+
+    let atomic = median_read_latency::<arcu::atomic::Arcu<u64, _>>();
+    assert!(atomic < threshold, "atomic read took {atomic:?}");
+
+    let rwlock = median_read_latency::<arcu::rwlock::Arcu<u64, _>>();
+    assert!(rwlock < threshold, "rwlock read took {rwlock:?}");
+}
@@ -0,0 +1,42 @@
+//! Kept separate from the other tests, as these count the globally registered epoch counters
+
+#![cfg(feature = "thread_local_counter")]
+
+use arcu::{
+    epoch_counters::{global_counters, GlobalEpochCounterPool},
+    scoped::ScopedReader,
+    Rcu,
+};
+
+#[test]
+fn scoped_reader_unregisters() {
+    let rcu = arcu::atomic::Arcu::new(0u32, GlobalEpochCounterPool);
+    // register the thread local epoch counter of the writing thread
+    assert_eq!(*rcu.read(), 0);
+    let registered = global_counters().len();
+
+    for round in 1..=3 {
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| {
+                    let reader = ScopedReader::new(scope);
+                    for _ in 0..100 {
+                        assert!(*reader.read(&rcu) >= round - 1);
+                    }
+                });
+            }
+            rcu.replace(round);
+        });
+
+        assert_eq!(global_counters().len(), registered);
+    }
+
+    std::thread::scope(|scope| {
+        let reader = ScopedReader::new(scope);
+        assert_eq!(global_counters().len(), registered + 1);
+        let value = reader.read(&rcu);
+        drop(reader);
+        assert_eq!(global_counters().len(), registered);
+        assert_eq!(*value, 3);
+    });
+}
@@ -0,0 +1,67 @@
+//! Checks that writers waiting on a slow reader back off instead of occupying a core
+//!
+//! Set `ARCU_SKIP_TIMING` to skip it, e.g. on slow or heavily shared CI machines.
+
+#![cfg(all(feature = "thread_local_counter", target_os = "linux"))]
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Barrier,
+    },
+    time::{Duration, Instant},
+};
+
+use arcu::{atomic::Arcu, epoch_counters::GlobalEpochCounterPool, Rcu};
+
+/// The cpu time used by the current thread, from the utime and stime fields of its stat
+fn thread_cpu_time() -> Duration {
+    let stat = std::fs::read_to_string("/proc/thread-self/stat").unwrap();
+    // the fields following the parenthesized command name, starting with the state as field 3
+    let fields = stat[stat.rfind(')').unwrap() + 2..]
+        .split(' ')
+        .collect::<Vec<_>>();
+    let ticks = fields[14 - 3].parse::<u64>().unwrap() + fields[15 - 3].parse::<u64>().unwrap();
+    // the clock ticks are reported in USER_HZ, which is 100 on all linux targets
+    Duration::from_millis(ticks * 10)
+}
+
+#[test]
+#[cfg_attr(miri, ignore = "timing is meaningless under miri")]
+fn writer_waiting_on_slow_reader_backs_off() {
+    if std::env::var_os("ARCU_SKIP_TIMING").is_some() {
+        return;
+    }
+
+    const HOLD: Duration = Duration::from_millis(300);
+
+    let rcu = Arcu::new(1u32, GlobalEpochCounterPool);
+    let entered = Barrier::new(2);
+    let left = AtomicBool::new(false);
+
+    std::thread::scope(|s| {
+        s.spawn(|| {
+            rcu.with_read_borrowed(|_| {
+                entered.wait();
+                std::thread::sleep(HOLD);
+                left.store(true, Ordering::Relaxed);
+            });
+        });
+
+        entered.wait();
+        let cpu_start = thread_cpu_time();
+        let start = Instant::now();
+        rcu.replace(2);
+        let wall = start.elapsed();
+        let cpu = thread_cpu_time() - cpu_start;
+
+        assert!(
+            left.load(Ordering::Relaxed),
+            "replace returned while the reader was reading"
+        );
+        assert!(
+            cpu < wall / 2,
+            "the writer used {cpu:?} of cpu time while waiting {wall:?} for the reader"
+        );
+    });
+}